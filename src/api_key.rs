@@ -0,0 +1,72 @@
+//! API key wrapper that keeps the key out of logs and error reports.
+
+use std::fmt;
+
+use reqwest::Url;
+
+/// Placeholder printed instead of the API key.
+const REDACTED: &str = "[REDACTED]";
+
+/// MailboxValidator API key.
+///
+/// `Debug` and `Display` never print the key itself; use
+/// [`ApiKey::expose_secret`] where the raw value is really needed.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::ApiKey;
+///
+/// let key = ApiKey::from("0123456789ABCDEF");
+/// assert_eq!(format!("{}", key), "[REDACTED]");
+/// assert_eq!(format!("{:?}", key), "ApiKey([REDACTED])");
+/// assert_eq!(key.expose_secret(), "0123456789ABCDEF");
+/// ```
+#[derive(PartialEq, Eq, Clone)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// Returns the raw API key.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey({})", REDACTED)
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        ApiKey(key.to_string())
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey(key)
+    }
+}
+
+/// Masks the `key` query parameter of `url` in place.
+pub(crate) fn redact_url(url: &mut Url) {
+    if !url.query_pairs().any(|(name, _)| name == "key") {
+        return;
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == "key" { REDACTED.into() } else { value };
+            (name.into_owned(), value.into_owned())
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
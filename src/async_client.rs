@@ -0,0 +1,266 @@
+//! Async MailboxValidator API client.
+
+use std::time::Duration;
+
+use futures_util::future::{self, Either};
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+
+use crate::client::{parse_response, ClientConfig};
+use crate::records::Record;
+use crate::retry::retry_after;
+use crate::{
+    ApiKey, BulkItem, BulkOptions, BulkOrder, CostEstimate, DisposableEmailRecord, Endpoint, FreeEmailRecord, MailboxValidatorClientBuilder, MailboxValidatorResult,
+    SingleEmailValidationRecord,
+};
+
+/// Async client for the MailboxValidator API.
+///
+/// Requires the `async` feature. The client holds a single connection pool,
+/// so build it once and reuse it. It is cheap to clone and can be shared
+/// between tasks.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run() -> Result<(), mailboxvalidator::MailboxValidatorError> {
+/// let client = mailboxvalidator::AsyncMailboxValidatorClient::new("YOUR_API_KEY")?;
+///
+/// let record = client.validate_email("example@example.com").await?;
+/// println!("status: {:?}, score: {}", record.status, record.mailboxvalidator_score);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncMailboxValidatorClient {
+    http: reqwest::Client,
+    config: ClientConfig,
+}
+
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AsyncMailboxValidatorClient>();
+};
+
+impl AsyncMailboxValidatorClient {
+    /// Creates a client with default settings.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the underlying HTTP client cannot be initialised.
+    pub fn new(api_key: impl Into<ApiKey>) -> MailboxValidatorResult<Self> {
+        Self::builder(api_key).build_async()
+    }
+
+    /// Starts building a client for the given API key.
+    ///
+    /// Finish with [`MailboxValidatorClientBuilder::build_async`].
+    pub fn builder(api_key: impl Into<ApiKey>) -> MailboxValidatorClientBuilder {
+        MailboxValidatorClientBuilder::new(api_key.into())
+    }
+
+    pub(crate) fn from_parts(http: reqwest::Client, config: ClientConfig) -> Self {
+        AsyncMailboxValidatorClient { http, config }
+    }
+
+    /// Validates email address using MailboxValidator Single Validation API.
+    ///
+    /// # Errors
+    ///
+    /// * Error when connecting to MailboxValidator API.
+    /// * Error object returned by MailboxValidator API.
+    /// * Unexpected response from MailboxValidator API.
+    pub async fn validate_email(&self, email_address: &str) -> MailboxValidatorResult<SingleEmailValidationRecord> {
+        let record = self.get(Endpoint::Single, email_address).await?;
+        Ok(self.config.suggest(email_address, record))
+    }
+
+    /// Checks email address using MailboxValidator Disposable Email API.
+    ///
+    /// Answered from the local domain list instead when the client was built
+    /// with a [`CheckMode`](crate::CheckMode) other than `Remote`.
+    ///
+    /// # Errors
+    ///
+    /// * Error when connecting to MailboxValidator API.
+    /// * Error object returned by MailboxValidator API.
+    /// * Unexpected response from MailboxValidator API.
+    pub async fn is_disposable_email(&self, email_address: &str) -> MailboxValidatorResult<DisposableEmailRecord> {
+        if let Some(result) = self.config.local_disposable(email_address) {
+            return result;
+        }
+        self.get(Endpoint::Disposable, email_address).await
+    }
+
+    /// Checks email address using MailboxValidator Free Email API.
+    ///
+    /// Answered from the local domain list instead when the client was built
+    /// with a [`CheckMode`](crate::CheckMode) other than `Remote`.
+    ///
+    /// # Errors
+    ///
+    /// * Error when connecting to MailboxValidator API.
+    /// * Error object returned by MailboxValidator API.
+    /// * Unexpected response from MailboxValidator API.
+    pub async fn is_free_email(&self, email_address: &str) -> MailboxValidatorResult<FreeEmailRecord> {
+        if let Some(result) = self.config.local_free(email_address) {
+            return result;
+        }
+        self.get(Endpoint::Free, email_address).await
+    }
+
+    /// Validates a stream of addresses using MailboxValidator Single
+    /// Validation API.
+    ///
+    /// Up to [`BulkOptions::concurrency`] requests run at once. Each result is
+    /// paired with its input address, and an error for one address does not
+    /// stop the others. Once the [`BulkOptions::pause_handle`] is paused, no
+    /// further addresses are taken from `emails`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use mailboxvalidator::{AsyncMailboxValidatorClient, BulkOptions, BulkOrder};
+    ///
+    /// # async fn run(emails: impl futures_util::Stream<Item = String>) -> Result<(), mailboxvalidator::MailboxValidatorError> {
+    /// let client = AsyncMailboxValidatorClient::new("YOUR_API_KEY")?;
+    /// let options = BulkOptions::new().concurrency(16).order(BulkOrder::Completion);
+    ///
+    /// let mut results = std::pin::pin!(client.validate_stream(emails, &options));
+    /// while let Some(item) = results.next().await {
+    ///     println!("{}: {:?}", item.email, item.result.map(|record| record.status));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_stream<'a, S>(&'a self, emails: S, options: &BulkOptions) -> impl Stream<Item = BulkItem> + 'a
+    where
+        S: Stream + 'a,
+        S::Item: Into<String>,
+    {
+        let paused = options.clone();
+        let emails = emails.take_while(move |_| future::ready(!paused.is_paused()));
+        let requests = emails.map(move |email| {
+            let email: String = email.into();
+            async move {
+                let result = self.validate_email(&email).await;
+                BulkItem { email, result }
+            }
+        });
+        match options.get_order() {
+            BulkOrder::Input => Either::Left(requests.buffered(options.get_concurrency())),
+            BulkOrder::Completion => Either::Right(requests.buffer_unordered(options.get_concurrency())),
+        }
+    }
+
+    /// Validates many addresses, see
+    /// [`AsyncMailboxValidatorClient::validate_stream`].
+    pub async fn validate_many<I, S>(&self, emails: I, options: &BulkOptions) -> Vec<BulkItem>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.validate_stream(stream::iter(emails), options).collect().await
+    }
+
+    /// Works out what validating `emails` with
+    /// [`AsyncMailboxValidatorClient::validate_many`] would cost, without
+    /// sending any request, see `MailboxValidatorClient::estimate`.
+    ///
+    /// Unlike validations, this looks addresses up in the cache on the
+    /// calling thread. With a cache that does disk or network I/O, call it
+    /// through `tokio::task::spawn_blocking`.
+    pub fn estimate<I, S>(&self, emails: I) -> CostEstimate
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.estimate(emails, None)
+    }
+
+    /// Returns the credit balance reported by the latest API response, or
+    /// `None` before the first one, see
+    /// `MailboxValidatorClient::credits_remaining`.
+    pub fn credits_remaining(&self) -> Option<i64> {
+        self.config.credits.remaining()
+    }
+
+    /// Returns the URL requested for `email_address` on `endpoint`.
+    ///
+    /// Every query parameter is percent-encoded, see
+    /// `MailboxValidatorClient::request_url`. The URL contains the raw API
+    /// key; don't log it.
+    pub fn request_url(&self, endpoint: Endpoint, email_address: &str) -> Url {
+        self.config.url(endpoint, email_address)
+    }
+
+    async fn get<T>(&self, endpoint: Endpoint, email_address: &str) -> MailboxValidatorResult<T>
+    where
+        T: Record + Clone + Send + 'static,
+    {
+        if let Some(result) = self.config.check_syntax(email_address) {
+            return result;
+        }
+        if let Some(cache) = self.config.cache.clone() {
+            let email_address = email_address.to_string();
+            if let Some(record) = run_blocking(move || cache.get(endpoint, &email_address)).await.flatten() {
+                return Ok(record);
+            }
+        }
+        let mut attempt = 1;
+        loop {
+            let ticket = self.config.reserve_credit()?;
+            let (result, retry_after) = self.send::<T>(endpoint, email_address).await;
+            self.config.settle(ticket, &result);
+            let err = match result {
+                Ok(mut record) => {
+                    // The API echoes the address it was sent, with the domain in punycode.
+                    record.set_email_address(email_address);
+                    if let Some(cache) = self.config.cache.clone() {
+                        let email_address = email_address.to_string();
+                        let stored = record.clone();
+                        run_blocking(move || cache.put(endpoint, &email_address, &stored)).await;
+                    }
+                    return Ok(record);
+                }
+                Err(err) => err,
+            };
+            match self.config.retry.next_delay(attempt, &err, retry_after) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(err),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends one request, also returning the response's `Retry-After` delay.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        email_address: &str,
+    ) -> (MailboxValidatorResult<T>, Option<Duration>) {
+        if let Some(limiter) = &self.config.rate_limiter {
+            tokio::time::sleep(limiter.reserve()).await;
+        }
+        let res = match self.http.get(self.request_url(endpoint, email_address)).send().await {
+            Ok(res) => res,
+            Err(err) => return (Err(err.into()), None),
+        };
+        let status = res.status();
+        let retry_after = retry_after(res.headers());
+        let result = match res.text().await {
+            Ok(body) => parse_response(status, body),
+            Err(err) => Err(err.into()),
+        };
+        (result, retry_after)
+    }
+}
+
+/// Runs a cache operation on tokio's blocking thread pool, since the
+/// [`Cache`](crate::Cache) may wait on a disk or a network. Returns `None`
+/// if the operation panicked.
+async fn run_blocking<R: Send + 'static>(operation: impl FnOnce() -> R + Send + 'static) -> Option<R> {
+    tokio::task::spawn_blocking(operation).await.ok()
+}
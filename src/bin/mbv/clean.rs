@@ -0,0 +1,538 @@
+//! `mbv clean`: annotate every row of a CSV list with its validation result.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use csv::StringRecord;
+use mailboxvalidator::{
+    BulkOptions, BulkOrder, Checkpoint, CostEstimate, MailboxValidatorClient, MailboxValidatorError, PauseHandle,
+    ResultOrigin, SingleEmailValidationRecord,
+};
+
+use crate::fields;
+
+/// Prefix of the columns added to the output.
+const PREFIX: &str = "mbv_";
+
+/// Header names recognised as the email column, compared case-insensitively.
+const EMAIL_HEADERS: &[&str] = &["email", "e-mail", "email_address", "email address", "emailaddress", "mail"];
+
+/// Settings for one `mbv clean` run.
+pub struct CleanArgs {
+    pub input: PathBuf,
+    pub output: Option<PathBuf>,
+    pub column: Option<String>,
+    pub concurrency: usize,
+    pub checkpoint: Option<PathBuf>,
+    pub pause: PauseHandle,
+    /// Only estimate the cost of the run; send no request, write no output.
+    pub dry_run: bool,
+}
+
+/// Counts reported once the list is cleaned.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub valid: usize,
+    pub invalid: usize,
+    pub unknown: usize,
+    pub errors: usize,
+    /// Rows answered from the cache instead of the API.
+    pub cached: usize,
+    /// Addresses taken from the checkpoint instead of validated again.
+    pub resumed: usize,
+    /// Rows not validated because the credit budget was spent.
+    pub over_budget: usize,
+    /// Set when the run was paused before every address was validated. No
+    /// output is written in that case, to the output file or to standard
+    /// output.
+    pub paused: bool,
+    /// The expected cost, set instead of the counts above by a dry run.
+    pub estimate: Option<CostEstimate>,
+}
+
+/// Result for one row; `None` when the row has no email address.
+type RowResult<'a> = Option<Result<&'a SingleEmailValidationRecord, &'a MailboxValidatorError>>;
+
+pub fn run(client: &MailboxValidatorClient, args: &CleanArgs) -> Result<Summary, String> {
+    run_to(client, args, &mut io::stdout().lock())
+}
+
+/// Runs `mbv clean`, writing to `stdout` when there is no `-o`.
+fn run_to(client: &MailboxValidatorClient, args: &CleanArgs, stdout: &mut dyn Write) -> Result<Summary, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(&args.input)
+        .map_err(|err| format!("{}: {}", args.input.display(), err))?;
+    let headers = reader
+        .headers()
+        .map_err(|err| format!("{}: {}", args.input.display(), err))?
+        .clone();
+    let rows = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("{}: {}", args.input.display(), err))?;
+
+    let column = match &args.column {
+        Some(column) => find_column(&headers, column)
+            .ok_or_else(|| format!("column {:?} not found in {}", column, args.input.display()))?,
+        None => detect_column(&headers, &rows)
+            .ok_or_else(|| format!("no email column found in {}, use --column", args.input.display()))?,
+    };
+
+    let email_rows: Vec<usize> = (0..rows.len())
+        .filter(|&index| !email_of(&rows[index], column).is_empty())
+        .collect();
+    let emails: Vec<String> = email_rows
+        .iter()
+        .map(|&index| email_of(&rows[index], column).to_string())
+        .collect();
+    if args.dry_run {
+        // Never create the checkpoint just to estimate.
+        let estimate = match args.checkpoint.as_deref().filter(|path| path.exists()) {
+            Some(path) => {
+                let checkpoint = Checkpoint::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+                client.estimate_resumable(emails, &checkpoint)
+            }
+            None => client.estimate(emails),
+        };
+        return Ok(Summary {
+            estimate: Some(estimate),
+            ..Summary::default()
+        });
+    }
+    let options = BulkOptions::new()
+        .concurrency(args.concurrency)
+        .order(BulkOrder::Input)
+        .pause_handle(args.pause.clone());
+
+    // With `-o`, write next to the output and rename once complete, so an
+    // interrupted run never leaves a half-written file behind. Without it,
+    // hold the rows until then for the same reason.
+    let temp = args.output.as_deref().map(temp_path);
+    let mut writer = csv::Writer::from_writer(open_output(temp.as_deref())?);
+    let mut output_headers = headers.clone();
+    output_headers.push_field(&format!("{}error", PREFIX));
+    for name in fields::SINGLE_COLUMNS {
+        output_headers.push_field(&format!("{}{}", PREFIX, name));
+    }
+    let mut write_error = writer.write_record(&output_headers).err().map(|err| err.to_string());
+    let mut summary = Summary::default();
+
+    match &args.checkpoint {
+        Some(path) => {
+            let mut checkpoint = Checkpoint::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            let report = client
+                .validate_resumable(emails, &options, &mut checkpoint)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+            summary.resumed = report.skipped;
+            summary.paused = report.paused;
+            if !summary.paused {
+                let failures: HashMap<&str, &MailboxValidatorError> = report
+                    .failures
+                    .iter()
+                    .filter_map(|item| item.result.as_ref().err().map(|err| (item.email.as_str(), err)))
+                    .collect();
+                for row in &rows {
+                    let email = email_of(row, column);
+                    let result = match checkpoint.get(email) {
+                        Some(record) => Some(Ok(record)),
+                        None => failures.get(email).map(|err| Err(*err)),
+                    };
+                    record_row(&mut writer, row, result, &mut summary, &mut write_error);
+                }
+            }
+        }
+        None => {
+            let mut next_row = 0;
+            let mut next_item = 0;
+            client.validate_each(emails, &options, |item| {
+                let row = email_rows[next_item];
+                next_item += 1;
+                while next_row < row {
+                    record_row(&mut writer, &rows[next_row], None, &mut summary, &mut write_error);
+                    next_row += 1;
+                }
+                record_row(&mut writer, &rows[row], Some(item.result.as_ref()), &mut summary, &mut write_error);
+                next_row = row + 1;
+                // Stop spending credits on rows that can no longer be written.
+                if write_error.is_some() {
+                    args.pause.pause();
+                }
+            });
+            summary.paused = next_item < email_rows.len();
+            while next_row < rows.len() && !summary.paused {
+                record_row(&mut writer, &rows[next_row], None, &mut summary, &mut write_error);
+                next_row += 1;
+            }
+        }
+    }
+
+    let finished = match write_error {
+        Some(err) => Err(err),
+        None => writer.into_inner().map_err(|err| err.error().to_string()),
+    };
+    let buffer = match finished {
+        Ok(Output::Buffer(buffer)) => Some(buffer),
+        Ok(Output::File(file)) => {
+            drop(file);
+            None
+        }
+        Err(err) => {
+            if let Some(temp) = &temp {
+                let _ = fs::remove_file(temp);
+            }
+            return Err(err);
+        }
+    };
+    if summary.paused {
+        if let Some(temp) = &temp {
+            let _ = fs::remove_file(temp);
+        }
+        return Ok(summary);
+    }
+    if let (Some(temp), Some(output)) = (&temp, &args.output) {
+        fs::rename(temp, output).map_err(|err| format!("{}: {}", output.display(), err))?;
+    }
+    if let Some(buffer) = buffer {
+        stdout
+            .write_all(&buffer)
+            .and_then(|()| stdout.flush())
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(summary)
+}
+
+/// Writes one annotated row, keeping the first write error.
+fn record_row<W: Write>(
+    writer: &mut csv::Writer<W>,
+    row: &StringRecord,
+    result: RowResult,
+    summary: &mut Summary,
+    write_error: &mut Option<String>,
+) {
+    let mut output = row.clone();
+    match result {
+        Some(Ok(record)) => {
+            if record.origin == ResultOrigin::Cache {
+                summary.cached += 1;
+            }
+            match record.status {
+                Some(true) => summary.valid += 1,
+                Some(false) => summary.invalid += 1,
+                None => summary.unknown += 1,
+            }
+            output.push_field("");
+            for (_, value) in fields::single(record) {
+                output.push_field(&value);
+            }
+        }
+        failed => {
+            summary.errors += 1;
+            if let Some(Err(MailboxValidatorError::BudgetExhausted)) = failed {
+                summary.over_budget += 1;
+            }
+            let message = match failed {
+                Some(Err(err)) => err.to_string(),
+                _ => "missing email address".to_string(),
+            };
+            output.push_field(&message);
+            for _ in fields::SINGLE_COLUMNS {
+                output.push_field("");
+            }
+        }
+    }
+
+    if write_error.is_none() {
+        if let Err(err) = writer.write_record(&output) {
+            *write_error = Some(err.to_string());
+        }
+    }
+}
+
+fn email_of(row: &StringRecord, column: usize) -> &str {
+    row.get(column).unwrap_or("").trim()
+}
+
+/// Finds a column by header name, or by 1-based position.
+fn find_column(headers: &StringRecord, column: &str) -> Option<usize> {
+    headers
+        .iter()
+        .position(|header| header.trim().eq_ignore_ascii_case(column.trim()))
+        .or_else(|| {
+            column
+                .parse::<usize>()
+                .ok()
+                .filter(|&position| position >= 1 && position <= headers.len())
+                .map(|position| position - 1)
+        })
+}
+
+/// Picks the email column by header name, or else the first column whose
+/// first non-empty value looks like an address.
+fn detect_column(headers: &StringRecord, rows: &[StringRecord]) -> Option<usize> {
+    if let Some(column) = headers
+        .iter()
+        .position(|header| EMAIL_HEADERS.iter().any(|name| header.trim().eq_ignore_ascii_case(name)))
+    {
+        return Some(column);
+    }
+    (0..headers.len()).find(|&column| {
+        rows.iter()
+            .map(|row| email_of(row, column))
+            .find(|value| !value.is_empty())
+            .is_some_and(|value| value.contains('@'))
+    })
+}
+
+/// Temporary file the output is written to before being renamed into place.
+fn temp_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    output.with_file_name(name)
+}
+
+/// Where the annotated rows are written until the run completes.
+enum Output {
+    /// The temporary file next to `-o`.
+    File(io::BufWriter<File>),
+    /// Memory, copied to standard output once complete.
+    Buffer(Vec<u8>),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::File(file) => file.write(buf),
+            Output::Buffer(buffer) => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::File(file) => file.flush(),
+            Output::Buffer(_) => Ok(()),
+        }
+    }
+}
+
+fn open_output(path: Option<&Path>) -> Result<Output, String> {
+    match path {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            Ok(Output::File(io::BufWriter::new(file)))
+        }
+        None => Ok(Output::Buffer(Vec::new())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::{env, fs, process};
+
+    use csv::StringRecord;
+    use mailboxvalidator::{Budget, MailboxValidatorClient, PauseHandle};
+
+    use super::{detect_column, find_column, run, run_to, temp_path, CleanArgs, PREFIX};
+
+    fn record(fields: &[&str]) -> StringRecord {
+        StringRecord::from(fields.to_vec())
+    }
+
+    /// A path in the temporary directory, unique to this process and test.
+    fn scratch(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("mbv-clean-{}-{}", process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// Answers malformed addresses locally and refuses to send the rest, so
+    /// no request leaves the machine.
+    fn offline_client() -> MailboxValidatorClient {
+        MailboxValidatorClient::builder("YOUR_API_KEY")
+            .base_url("http://127.0.0.1:1/v2/")
+            .check_syntax(true)
+            .budget(Budget::new(0))
+            .build()
+            .unwrap()
+    }
+
+    fn args(input: PathBuf, output: PathBuf, checkpoint: Option<PathBuf>) -> CleanArgs {
+        CleanArgs {
+            input,
+            output: Some(output),
+            column: Some("contact".to_string()),
+            concurrency: 3,
+            checkpoint,
+            pause: PauseHandle::new(),
+            dry_run: false,
+        }
+    }
+
+    const INPUT: &str = "\
+name,contact,notes
+\"Doe, Jane\",not an address,first
+Blank,,\"multi
+line\"
+\"Smith, \"\"Bob\"\"\",bob@example.com,
+Repeat,not an address,
+Spaces,  ,
+Again,bob@example.com,
+";
+
+    /// The output rows as (name, notes, mbv_error, mbv_status).
+    fn read_output(path: &PathBuf) -> Vec<(String, String, String, String)> {
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        let error = headers.iter().position(|header| header == format!("{}error", PREFIX)).unwrap();
+        let status = headers.iter().position(|header| header == format!("{}status", PREFIX)).unwrap();
+        assert_eq!(&headers.iter().take(3).collect::<Vec<_>>(), &["name", "contact", "notes"]);
+        reader
+            .records()
+            .map(|row| {
+                let row = row.unwrap();
+                (row[0].to_string(), row[2].to_string(), row[error].to_string(), row[status].to_string())
+            })
+            .collect()
+    }
+
+    fn assert_aligned(rows: &[(String, String, String, String)]) {
+        let names: Vec<_> = rows.iter().map(|row| row.0.as_str()).collect();
+        assert_eq!(names, ["Doe, Jane", "Blank", "Smith, \"Bob\"", "Repeat", "Spaces", "Again"]);
+        assert_eq!(rows[1].1, "multi\nline");
+        for index in [0, 3] {
+            assert_eq!((rows[index].2.as_str(), rows[index].3.as_str()), ("", "false"));
+        }
+        for index in [1, 4] {
+            assert_eq!((rows[index].2.as_str(), rows[index].3.as_str()), ("missing email address", ""));
+        }
+        for index in [2, 5] {
+            assert!(rows[index].2.contains("budget"), "{:?}", rows[index]);
+            assert_eq!(rows[index].3, "");
+        }
+    }
+
+    #[test]
+    fn finds_column_by_name_or_position() {
+        let headers = record(&["Name", " E-Mail ", "Phone"]);
+        assert_eq!(find_column(&headers, "e-mail"), Some(1));
+        assert_eq!(find_column(&headers, " PHONE"), Some(2));
+        assert_eq!(find_column(&headers, "1"), Some(0));
+        assert_eq!(find_column(&headers, "3"), Some(2));
+        assert_eq!(find_column(&headers, "0"), None);
+        assert_eq!(find_column(&headers, "4"), None);
+        assert_eq!(find_column(&headers, "email"), None);
+    }
+
+    #[test]
+    fn detects_column_by_header() {
+        let headers = record(&["name", "Email Address", "backup"]);
+        let rows = [record(&["Jane", "not yet", "jane@example.com"])];
+        assert_eq!(detect_column(&headers, &rows), Some(1));
+    }
+
+    #[test]
+    fn detects_column_by_first_non_empty_value() {
+        let headers = record(&["name", "primary", "backup"]);
+        let rows = [
+            record(&["Jane", "", " "]),
+            record(&["Bob", "", "bob@example.com"]),
+            record(&["Ann", "ann@example.com", "none"]),
+            record(&["Short"]),
+        ];
+        assert_eq!(detect_column(&headers, &rows), Some(1));
+
+        let rows = [record(&["Jane", "n/a", "jane@example.com"])];
+        assert_eq!(detect_column(&headers, &rows), Some(2));
+        assert_eq!(detect_column(&headers, &[record(&["Jane", "n/a", "none"])]), None);
+        assert_eq!(detect_column(&headers, &[]), None);
+    }
+
+    #[test]
+    fn keeps_rows_aligned() {
+        let input = scratch("aligned.csv");
+        let output = scratch("aligned-out.csv");
+        fs::write(&input, INPUT).unwrap();
+
+        let summary = run(&offline_client(), &args(input.clone(), output.clone(), None)).unwrap();
+        assert_eq!((summary.invalid, summary.errors, summary.over_budget), (2, 4, 2));
+        assert!(!summary.paused);
+        assert_aligned(&read_output(&output));
+        assert!(!temp_path(&output).exists());
+
+        for path in [input, output] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn keeps_rows_aligned_with_checkpoint() {
+        let input = scratch("checkpoint.csv");
+        let output = scratch("checkpoint-out.csv");
+        let checkpoint = scratch("checkpoint.jsonl");
+        fs::write(&input, INPUT).unwrap();
+
+        // The budget pauses the run before any output is written.
+        let summary = run(&offline_client(), &args(input.clone(), output.clone(), Some(checkpoint.clone()))).unwrap();
+        assert!(summary.paused);
+        assert!(!output.exists() && !temp_path(&output).exists());
+
+        let args = args(input.clone(), output.clone(), Some(checkpoint.clone()));
+        let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+            .base_url("http://127.0.0.1:1/v2/")
+            .check_syntax(true)
+            .build()
+            .unwrap();
+        let summary = run(&client, &args).unwrap();
+        assert_eq!((summary.resumed, summary.invalid, summary.errors), (2, 2, 4));
+        let rows = read_output(&output);
+        assert_eq!(rows.len(), 6);
+        assert_eq!((rows[0].2.as_str(), rows[3].3.as_str()), ("", "false"));
+        assert_eq!(rows[1].2, "missing email address");
+        assert!(!rows[2].2.is_empty() && rows[2].2 == rows[5].2, "{:?}", rows);
+
+        for path in [input, output, checkpoint] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn pausing_removes_the_partial_output() {
+        let input = scratch("paused.csv");
+        let output = scratch("paused-out.csv");
+        fs::write(&input, INPUT).unwrap();
+
+        let args = args(input.clone(), output.clone(), None);
+        args.pause.pause();
+        let summary = run(&offline_client(), &args).unwrap();
+        assert!(summary.paused);
+        assert!(!output.exists() && !temp_path(&output).exists());
+
+        let _ = fs::remove_file(input);
+    }
+
+    #[test]
+    fn writes_standard_output_only_once_complete() {
+        let input = scratch("stdout.csv");
+        fs::write(&input, INPUT).unwrap();
+        let mut args = args(input.clone(), PathBuf::new(), None);
+        args.output = None;
+
+        let mut stdout = Vec::new();
+        let summary = run_to(&offline_client(), &args, &mut stdout).unwrap();
+        assert!(!summary.paused);
+        let text = String::from_utf8(stdout).unwrap();
+        assert!(text.starts_with("name,contact,notes,mbv_error,"), "{}", text);
+        assert_eq!(csv::Reader::from_reader(text.as_bytes()).records().count(), 6);
+
+        args.pause.pause();
+        let mut stdout = Vec::new();
+        let summary = run_to(&offline_client(), &args, &mut stdout).unwrap();
+        assert!(summary.paused);
+        assert!(stdout.is_empty());
+
+        let _ = fs::remove_file(input);
+    }
+}
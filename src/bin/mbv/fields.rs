@@ -0,0 +1,61 @@
+//! Flattens result records into named text fields for display.
+
+use mailboxvalidator::SingleEmailValidationRecord;
+
+/// Names of the fields returned by [`single`], in order.
+pub const SINGLE_COLUMNS: [&str; 21] = [
+    "status",
+    "mailboxvalidator_score",
+    "base_email_address",
+    "domain",
+    "is_free",
+    "is_syntax",
+    "is_domain",
+    "is_smtp",
+    "is_verified",
+    "is_server_down",
+    "is_greylisted",
+    "is_disposable",
+    "is_suppressed",
+    "is_role",
+    "is_high_risk",
+    "is_catchall",
+    "is_dmarc_enforced",
+    "is_strict_spf",
+    "website_exist",
+    "time_taken",
+    "credits_available",
+];
+
+/// Formats an optional flag, leaving it empty when not applicable.
+pub fn flag(value: Option<bool>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Every field of a single validation record except the input address.
+pub fn single(record: &SingleEmailValidationRecord) -> Vec<(&'static str, String)> {
+    let values = [
+        flag(record.status),
+        record.mailboxvalidator_score.to_string(),
+        record.base_email_address.clone(),
+        record.domain.clone(),
+        flag(record.is_free),
+        flag(record.is_syntax),
+        flag(record.is_domain),
+        flag(record.is_smtp),
+        flag(record.is_verified),
+        flag(record.is_server_down),
+        flag(record.is_greylisted),
+        flag(record.is_disposable),
+        flag(record.is_suppressed),
+        flag(record.is_role),
+        flag(record.is_high_risk),
+        flag(record.is_catchall),
+        flag(record.is_dmarc_enforced),
+        flag(record.is_strict_spf),
+        flag(record.website_exist),
+        record.time_taken.to_string(),
+        record.credits_available.to_string(),
+    ];
+    SINGLE_COLUMNS.into_iter().zip(values).collect()
+}
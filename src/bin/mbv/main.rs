@@ -0,0 +1,347 @@
+//! `mbv`: check email addresses with the MailboxValidator API from the
+//! command line.
+//!
+//! Exit codes: 0 valid, 1 invalid, 2 unknown, 3 API error, 4 other failure,
+//! 64 usage error. `mbv clean` exits with 0 once every row is written, even
+//! if some rows failed, and with 130 when interrupted, or out of budget
+//! with a checkpoint.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use mailboxvalidator::{
+    ApiErrorCode, Budget, CostEstimate, DisposableEmailRecord, ErrorRecord, ErrorRecord1, FreeEmailRecord,
+    MailboxValidatorClient, MailboxValidatorError, PauseHandle, SingleEmailValidationRecord, SqliteCache, DEFAULT_BASE_URL,
+};
+use serde::Serialize;
+
+mod clean;
+mod fields;
+
+/// Check email addresses with the MailboxValidator API.
+#[derive(Debug, Parser)]
+#[command(name = "mbv", version)]
+struct Cli {
+    /// MailboxValidator API key.
+    #[arg(long, env = "MBV_API_KEY", hide_env_values = true)]
+    api_key: String,
+
+    /// Print the raw result as JSON.
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// MailboxValidator API base URL.
+    #[arg(long, default_value = DEFAULT_BASE_URL)]
+    base_url: String,
+
+    /// Request timeout in seconds.
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+
+    /// Reject malformed addresses locally instead of sending them to the API.
+    #[arg(long, global = true)]
+    check_syntax: bool,
+
+    /// Most credits to spend; requests beyond it are refused without being sent.
+    #[arg(long, global = true, value_name = "CREDITS")]
+    budget: Option<u64>,
+
+    /// SQLite file to cache results in, shared between runs.
+    #[arg(long, env = "MBV_CACHE")]
+    cache: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Validate an address with the Single Validation API.
+    Validate {
+        /// The email address.
+        email: String,
+    },
+    /// Check whether an address is from a disposable email provider.
+    Disposable {
+        /// The email address.
+        email: String,
+    },
+    /// Check whether an address is from a free email provider.
+    Free {
+        /// The email address.
+        email: String,
+    },
+    /// Validate every address in a CSV file and write an annotated copy.
+    ///
+    /// The output keeps the original columns and adds `mbv_error` plus one
+    /// `mbv_` column per field of the validation result.
+    Clean {
+        /// The CSV file to clean. Its first row must be a header.
+        input: PathBuf,
+        /// Where to write the annotated CSV. Defaults to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Header name or 1-based position of the email column. Detected
+        /// automatically if omitted.
+        #[arg(long)]
+        column: Option<String>,
+        /// Number of requests in flight at once.
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// File recording every validated address. If it exists, addresses
+        /// it already holds are not validated again. With it, Ctrl-C pauses
+        /// the run; run the same command again to resume.
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        /// Report how many API calls and credits the run would need, after
+        /// duplicates, cache hits, local checks and the checkpoint, without
+        /// sending any request or writing any output.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Process exit codes.
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Valid = 0,
+    Invalid = 1,
+    Unknown = 2,
+    ApiError = 3,
+    Failure = 4,
+    Usage = 64,
+    Interrupted = 130,
+}
+
+impl Outcome {
+    fn from_flag(valid: Option<bool>) -> Self {
+        match valid {
+            Some(true) => Outcome::Valid,
+            Some(false) => Outcome::Invalid,
+            None => Outcome::Unknown,
+        }
+    }
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        ExitCode::from(outcome as u8)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() => {
+            let _ = err.print();
+            return Outcome::Usage.into();
+        }
+        Err(err) => {
+            let _ = err.print();
+            return Outcome::Valid.into();
+        }
+    };
+
+    let mut builder = MailboxValidatorClient::builder(cli.api_key.as_str())
+        .base_url(cli.base_url.as_str())
+        .timeout(Duration::from_secs(cli.timeout))
+        .check_syntax(cli.check_syntax);
+    let budget = cli.budget.map(Budget::new);
+    if let Some(budget) = &budget {
+        builder = builder.budget(budget.clone());
+    }
+    if let Some(path) = &cli.cache {
+        match SqliteCache::open(path) {
+            Ok(cache) => builder = builder.cache(cache),
+            Err(err) => {
+                eprintln!("mbv: {}: {}", path.display(), err);
+                return Outcome::Failure.into();
+            }
+        }
+    }
+    let client = match builder.build() {
+        Ok(client) => client,
+        Err(err) => return report_error(&err, cli.json).into(),
+    };
+
+    let outcome = match &cli.command {
+        Command::Validate { email } => client
+            .validate_email(email)
+            .map(|record| print_single(&record, cli.json)),
+        Command::Disposable { email } => client
+            .is_disposable_email(email)
+            .map(|record| print_disposable(&record, cli.json)),
+        Command::Free { email } => client
+            .is_free_email(email)
+            .map(|record| print_free(&record, cli.json)),
+        Command::Clean {
+            input,
+            output,
+            column,
+            concurrency,
+            checkpoint,
+            dry_run,
+        } => {
+            let pause = PauseHandle::new();
+            if !dry_run {
+                let handler = pause.clone();
+                if let Err(err) = ctrlc::set_handler(move || {
+                    eprintln!("mbv: pausing, waiting for requests in flight");
+                    handler.pause();
+                }) {
+                    eprintln!("mbv: {}", err);
+                    return Outcome::Failure.into();
+                }
+            }
+            let args = clean::CleanArgs {
+                input: input.clone(),
+                output: output.clone(),
+                column: column.clone(),
+                concurrency: *concurrency,
+                checkpoint: checkpoint.clone(),
+                pause,
+                dry_run: *dry_run,
+            };
+            return match clean::run(&client, &args) {
+                Ok(clean::Summary {
+                    estimate: Some(estimate),
+                    ..
+                }) => {
+                    print_estimate(&estimate, budget.as_ref());
+                    Outcome::Valid.into()
+                }
+                Ok(summary)
+                    if summary.paused && checkpoint.is_some() && budget.as_ref().is_some_and(Budget::is_exhausted) =>
+                {
+                    eprintln!("mbv: credit budget spent, run the same command with a larger --budget to resume");
+                    Outcome::Interrupted.into()
+                }
+                Ok(summary) if summary.paused && checkpoint.is_some() => {
+                    eprintln!("mbv: paused, run the same command again to resume");
+                    Outcome::Interrupted.into()
+                }
+                Ok(summary) if summary.paused => {
+                    eprintln!("mbv: interrupted, no output written");
+                    Outcome::Interrupted.into()
+                }
+                Ok(summary) => {
+                    if summary.cached > 0 {
+                        eprintln!("mbv: {} addresses answered from the cache", summary.cached);
+                    }
+                    if summary.resumed > 0 {
+                        eprintln!("mbv: {} addresses taken from the checkpoint", summary.resumed);
+                    }
+                    eprintln!(
+                        "mbv: {} valid, {} invalid, {} unknown, {} failed",
+                        summary.valid, summary.invalid, summary.unknown, summary.errors
+                    );
+                    if summary.over_budget > 0 {
+                        eprintln!("mbv: credit budget spent, {} addresses not validated", summary.over_budget);
+                    }
+                    Outcome::Valid.into()
+                }
+                Err(err) => {
+                    eprintln!("mbv: {}", err);
+                    Outcome::Failure.into()
+                }
+            };
+        }
+    };
+
+    match outcome {
+        Ok(outcome) => outcome.into(),
+        Err(err) => report_error(&err, cli.json).into(),
+    }
+}
+
+fn print_estimate(estimate: &CostEstimate, budget: Option<&Budget>) {
+    eprintln!(
+        "mbv: {} addresses: {} from the checkpoint, {} duplicates, {} rejected locally, {} cached",
+        estimate.total, estimate.resumed, estimate.duplicates, estimate.local, estimate.cached
+    );
+    eprintln!("mbv: {} API calls would be sent, spending {} credits", estimate.api_calls, estimate.api_calls);
+    if let Some(budget) = budget {
+        if estimate.api_calls as u64 > budget.remaining() {
+            eprintln!("mbv: exceeds the budget of {} credits", budget.remaining());
+        }
+    }
+}
+
+fn print_single(record: &SingleEmailValidationRecord, json: bool) -> Outcome {
+    let outcome = Outcome::from_flag(record.status);
+    if json {
+        print_json(record);
+    } else {
+        println!("{}: {}", record.email_address, describe(outcome, "valid", "invalid"));
+        print_fields(&fields::single(record));
+    }
+    outcome
+}
+
+fn print_disposable(record: &DisposableEmailRecord, json: bool) -> Outcome {
+    let outcome = Outcome::from_flag(record.is_disposable.map(|disposable| !disposable));
+    if json {
+        print_json(record);
+    } else {
+        println!("{}: {}", record.email_address, describe(outcome, "not disposable", "disposable"));
+        print_fields(&[("credits_available", record.credits_available.to_string())]);
+    }
+    outcome
+}
+
+fn print_free(record: &FreeEmailRecord, json: bool) -> Outcome {
+    let outcome = Outcome::from_flag(record.is_free.map(|free| !free));
+    if json {
+        print_json(record);
+    } else {
+        println!("{}: {}", record.email_address, describe(outcome, "not free", "free"));
+        print_fields(&[("credits_available", record.credits_available.to_string())]);
+    }
+    outcome
+}
+
+fn describe(outcome: Outcome, valid: &'static str, invalid: &'static str) -> &'static str {
+    match outcome {
+        Outcome::Valid => valid,
+        Outcome::Invalid => invalid,
+        _ => "unknown",
+    }
+}
+
+fn print_fields(fields: &[(&str, String)]) {
+    let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in fields {
+        let value = if value.is_empty() { "-" } else { value };
+        println!("  {:width$}  {}", name, value, width = width);
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(err) => eprintln!("mbv: {}", err),
+    }
+}
+
+fn report_error(err: &MailboxValidatorError, json: bool) -> Outcome {
+    let error = match err {
+        MailboxValidatorError::Api(error) => error.clone(),
+        // Reported as the API would have reported the address.
+        MailboxValidatorError::InvalidSyntax(_) => ErrorRecord1 {
+            error_code: ApiErrorCode::InvalidSyntax,
+            error_message: err.to_string(),
+        },
+        _ => {
+            eprintln!("mbv: {}", err);
+            return Outcome::Failure;
+        }
+    };
+    if json {
+        print_json(&ErrorRecord { error });
+    } else {
+        eprintln!("mbv: {}", err);
+    }
+    Outcome::ApiError
+}
@@ -0,0 +1,433 @@
+//! Blocking MailboxValidator API client.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+
+use crate::client::{parse_response, ClientConfig};
+use crate::records::Record;
+use crate::retry::retry_after;
+use crate::{
+    ApiKey, BulkItem, BulkOptions, BulkOrder, Checkpoint, CostEstimate, DisposableEmailRecord, Endpoint, FreeEmailRecord, JobReport, MailboxValidatorClientBuilder, MailboxValidatorError, MailboxValidatorResult,
+    SingleEmailValidationRecord,
+};
+
+/// Blocking client for the MailboxValidator API.
+///
+/// The client holds a single connection pool, so build it once and reuse it.
+/// It is cheap to clone and can be shared between threads.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// let client = mailboxvalidator::MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .timeout(Duration::from_secs(20))
+///     .build()?;
+///
+/// let record = client.validate_email("example@example.com")?;
+/// println!("status: {:?}, score: {}", record.status, record.mailboxvalidator_score);
+/// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MailboxValidatorClient {
+    http: reqwest::blocking::Client,
+    config: ClientConfig,
+}
+
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MailboxValidatorClient>();
+};
+
+impl MailboxValidatorClient {
+    /// Creates a client with default settings.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the underlying HTTP client cannot be initialised.
+    pub fn new(api_key: impl Into<ApiKey>) -> MailboxValidatorResult<Self> {
+        Self::builder(api_key).build()
+    }
+
+    /// Starts building a client for the given API key.
+    pub fn builder(api_key: impl Into<ApiKey>) -> MailboxValidatorClientBuilder {
+        MailboxValidatorClientBuilder::new(api_key.into())
+    }
+
+    pub(crate) fn from_parts(http: reqwest::blocking::Client, config: ClientConfig) -> Self {
+        MailboxValidatorClient { http, config }
+    }
+
+    /// Validates email address using MailboxValidator Single Validation API.
+    ///
+    /// # Errors
+    ///
+    /// * Error when connecting to MailboxValidator API.
+    /// * Error object returned by MailboxValidator API.
+    /// * Unexpected response from MailboxValidator API.
+    pub fn validate_email(&self, email_address: &str) -> MailboxValidatorResult<SingleEmailValidationRecord> {
+        let record = self.get(Endpoint::Single, email_address)?;
+        Ok(self.config.suggest(email_address, record))
+    }
+
+    /// Checks email address using MailboxValidator Disposable Email API.
+    ///
+    /// Answered from the local domain list instead when the client was built
+    /// with a [`CheckMode`](crate::CheckMode) other than `Remote`.
+    ///
+    /// # Errors
+    ///
+    /// * Error when connecting to MailboxValidator API.
+    /// * Error object returned by MailboxValidator API.
+    /// * Unexpected response from MailboxValidator API.
+    pub fn is_disposable_email(&self, email_address: &str) -> MailboxValidatorResult<DisposableEmailRecord> {
+        if let Some(result) = self.config.local_disposable(email_address) {
+            return result;
+        }
+        self.get(Endpoint::Disposable, email_address)
+    }
+
+    /// Checks email address using MailboxValidator Free Email API.
+    ///
+    /// Answered from the local domain list instead when the client was built
+    /// with a [`CheckMode`](crate::CheckMode) other than `Remote`.
+    ///
+    /// # Errors
+    ///
+    /// * Error when connecting to MailboxValidator API.
+    /// * Error object returned by MailboxValidator API.
+    /// * Unexpected response from MailboxValidator API.
+    pub fn is_free_email(&self, email_address: &str) -> MailboxValidatorResult<FreeEmailRecord> {
+        if let Some(result) = self.config.local_free(email_address) {
+            return result;
+        }
+        self.get(Endpoint::Free, email_address)
+    }
+
+    /// Validates many addresses using MailboxValidator Single Validation API.
+    ///
+    /// Up to [`BulkOptions::concurrency`] requests run at once. Each result is
+    /// paired with its input address, and an error for one address does not
+    /// stop the others. Once the client's [`Budget`](crate::Budget) is
+    /// spent, the remaining addresses fail with
+    /// [`MailboxValidatorError::BudgetExhausted`] without being sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mailboxvalidator::{BulkOptions, MailboxValidatorClient};
+    ///
+    /// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+    ///     .base_url("http://127.0.0.1:1/v2/")
+    ///     .build()?;
+    ///
+    /// let items = client.validate_many(["a@example.com", "b@example.com", "c@example.com"], &BulkOptions::new());
+    /// let emails: Vec<_> = items.iter().map(|item| item.email.as_str()).collect();
+    /// assert_eq!(emails, ["a@example.com", "b@example.com", "c@example.com"]);
+    /// assert!(items.iter().all(|item| item.result.is_err()));
+    /// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+    /// ```
+    pub fn validate_many<I, S>(&self, emails: I, options: &BulkOptions) -> Vec<BulkItem>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: Send,
+        S: Into<String>,
+    {
+        let mut items = Vec::new();
+        self.validate_each(emails, options, |item| items.push(item));
+        items
+    }
+
+    /// Like [`MailboxValidatorClient::validate_many`], but hands each result
+    /// to `on_item` on the calling thread as soon as it is available.
+    pub fn validate_each<I, S, F>(&self, emails: I, options: &BulkOptions, mut on_item: F)
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: Send,
+        S: Into<String>,
+        F: FnMut(BulkItem),
+    {
+        let emails = Mutex::new(emails.into_iter().enumerate());
+        let (tx, rx) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..options.get_concurrency() {
+                let tx = tx.clone();
+                let emails = &emails;
+                scope.spawn(move || loop {
+                    if options.is_paused() {
+                        break;
+                    }
+                    let next = emails.lock().unwrap_or_else(PoisonError::into_inner).next();
+                    let Some((index, email)) = next else {
+                        break;
+                    };
+                    let email: String = email.into();
+                    let result = self.validate_email(&email);
+                    if tx.send((index, BulkItem { email, result })).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(tx);
+
+            let mut pending = BTreeMap::new();
+            let mut next_index = 0;
+            for (index, item) in rx {
+                match options.get_order() {
+                    BulkOrder::Completion => on_item(item),
+                    BulkOrder::Input => {
+                        pending.insert(index, item);
+                        while let Some(item) = pending.remove(&next_index) {
+                            on_item(item);
+                            next_index += 1;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Validates many addresses, storing every result in `checkpoint` as it
+    /// arrives and skipping addresses the checkpoint already holds.
+    ///
+    /// If the job is interrupted, by a crash or through the
+    /// [`BulkOptions::pause_handle`], calling this again with the same
+    /// checkpoint resumes it without paying for finished addresses again.
+    /// Duplicate addresses are validated once. Failed addresses are reported
+    /// in the [`JobReport`] and not stored, so they are retried next time.
+    /// The run also pauses once the client's [`Budget`](crate::Budget) is
+    /// spent.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use mailboxvalidator::{BulkOptions, Checkpoint, MailboxValidatorClient, PauseHandle};
+    ///
+    /// let client = MailboxValidatorClient::new("YOUR_API_KEY")?;
+    /// let mut checkpoint = Checkpoint::open("list.checkpoint.jsonl")?;
+    /// let pause = PauseHandle::new();
+    /// let options = BulkOptions::new().pause_handle(pause.clone());
+    ///
+    /// let report = client.validate_resumable(["a@example.com", "b@example.com"], &options, &mut checkpoint)?;
+    /// for (email, record) in checkpoint.iter() {
+    ///     println!("{}: {:?}", email, record.status);
+    /// }
+    /// println!("{} failed, paused: {}", report.failures.len(), report.paused);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Error when the checkpoint file cannot be written. The run stops at
+    ///   the first such error and pauses the options' pause handle, if any.
+    pub fn validate_resumable<I, S>(
+        &self,
+        emails: I,
+        options: &BulkOptions,
+        checkpoint: &mut Checkpoint,
+    ) -> io::Result<JobReport>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut report = JobReport::default();
+        let mut seen = HashSet::new();
+        let mut pending = Vec::new();
+        for email in emails {
+            let email: String = email.into();
+            if checkpoint.contains(&email) {
+                report.skipped += 1;
+            } else if seen.insert(email.clone()) {
+                pending.push(email);
+            }
+        }
+
+        // Stop sending requests on the first checkpoint write error.
+        let pause = options.pause.clone().unwrap_or_default();
+        let run_options = options.clone().pause_handle(pause.clone());
+        let mut write_error = None;
+        self.validate_each(pending, &run_options, |item| match item.result {
+            Ok(record) if write_error.is_none() => match checkpoint.record(&item.email, record) {
+                Ok(()) => report.validated += 1,
+                Err(err) => {
+                    write_error = Some(err);
+                    pause.pause();
+                }
+            },
+            Ok(_) => {}
+            Err(MailboxValidatorError::BudgetExhausted) => pause.pause(),
+            Err(_) => report.failures.push(item),
+        });
+
+        if let Some(err) = write_error {
+            return Err(err);
+        }
+        report.paused = pause.is_paused();
+        Ok(report)
+    }
+
+    /// Works out what validating `emails` with
+    /// [`MailboxValidatorClient::validate_many`] would cost, without sending
+    /// any request.
+    ///
+    /// Addresses the offline syntax check rejects or the cache holds are
+    /// free, and so are repeats of an earlier address's mailbox when the
+    /// client has a cache. Checking the cache may count as a lookup in its
+    /// statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mailboxvalidator::{MailboxValidatorClient, MemoryCache};
+    ///
+    /// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+    ///     .check_syntax(true)
+    ///     .cache(MemoryCache::new(1_000))
+    ///     .build()?;
+    ///
+    /// let emails = ["a@example.com", "A@Example.com", "not an address", "b@example.com"];
+    /// let estimate = client.estimate(emails);
+    /// assert_eq!((estimate.total, estimate.duplicates, estimate.local), (4, 1, 1));
+    /// assert_eq!(estimate.api_calls, 2);
+    /// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+    /// ```
+    pub fn estimate<I, S>(&self, emails: I) -> CostEstimate
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.estimate(emails, None)
+    }
+
+    /// Works out what [`MailboxValidatorClient::validate_resumable`] would
+    /// cost with `checkpoint`, without sending any request.
+    ///
+    /// Like [`MailboxValidatorClient::estimate`], but addresses the
+    /// checkpoint holds and exact repeats are free too.
+    pub fn estimate_resumable<I, S>(&self, emails: I, checkpoint: &Checkpoint) -> CostEstimate
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.estimate(emails, Some(checkpoint))
+    }
+
+    /// Returns the credit balance reported by the latest API response, or
+    /// `None` before the first one.
+    ///
+    /// Every clone of the client shares the balance. Results served from the
+    /// cache or decided locally leave it unchanged, and error 10004
+    /// (insufficient credits) sets it to 0. See
+    /// [`MailboxValidatorClientBuilder::low_balance_alert`](crate::MailboxValidatorClientBuilder::low_balance_alert)
+    /// to be told when it runs low.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mailboxvalidator::MailboxValidatorClient;
+    ///
+    /// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+    ///     .low_balance_alert(1_000, |credits| eprintln!("only {} credits left", credits))
+    ///     .build()?;
+    /// assert_eq!(client.credits_remaining(), None);
+    /// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+    /// ```
+    pub fn credits_remaining(&self) -> Option<i64> {
+        self.config.credits.remaining()
+    }
+
+    /// Returns the URL requested for `email_address` on `endpoint`.
+    ///
+    /// Every query parameter is percent-encoded, so addresses containing
+    /// `+`, `&`, quotes or non-ASCII characters reach the API unchanged,
+    /// except that internationalized domains are sent in their ASCII
+    /// (punycode) form. Records still carry `email_address` as given; use
+    /// `domain_ascii()` for the ASCII form. The URL contains the raw API
+    /// key; don't log it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mailboxvalidator::{Endpoint, MailboxValidatorClient};
+    ///
+    /// let client = MailboxValidatorClient::new("key&x=1")?;
+    /// let email_of = |email: &str| {
+    ///     let url = client.request_url(Endpoint::Single, email);
+    ///     let params: Vec<_> = url.query_pairs().into_owned().collect();
+    ///     assert_eq!(params.len(), 4);
+    ///     assert_eq!(params[1], ("key".to_string(), "key&x=1".to_string()));
+    ///     params[0].1.clone()
+    /// };
+    ///
+    /// for email in ["a+b@x.com", "\"odd&name\"@x.com", "'quoted'@x.com", "josé.ñandú@x.com"] {
+    ///     assert_eq!(email_of(email), email);
+    /// }
+    /// assert_eq!(email_of("用户@例子.广告"), "用户@xn--fsqu00a.xn--4rr70v");
+    ///
+    /// let url = client.request_url(Endpoint::Single, "a+b@x.com");
+    /// assert_eq!(url.path(), "/v2/validation/single");
+    /// assert!(url.query().unwrap().starts_with("email=a%2Bb%40x.com&key=key%26x%3D1&"));
+    /// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+    /// ```
+    pub fn request_url(&self, endpoint: Endpoint, email_address: &str) -> Url {
+        self.config.url(endpoint, email_address)
+    }
+
+    fn get<T: Record>(&self, endpoint: Endpoint, email_address: &str) -> MailboxValidatorResult<T> {
+        if let Some(result) = self.config.check_syntax(email_address) {
+            return result;
+        }
+        if let Some(record) = self.config.cache.as_ref().and_then(|cache| cache.get(endpoint, email_address)) {
+            return Ok(record);
+        }
+        let mut attempt = 1;
+        loop {
+            let ticket = self.config.reserve_credit()?;
+            let (result, retry_after) = self.send::<T>(endpoint, email_address);
+            self.config.settle(ticket, &result);
+            let err = match result {
+                Ok(mut record) => {
+                    // The API echoes the address it was sent, with the domain in punycode.
+                    record.set_email_address(email_address);
+                    if let Some(cache) = &self.config.cache {
+                        cache.put(endpoint, email_address, &record);
+                    }
+                    return Ok(record);
+                }
+                Err(err) => err,
+            };
+            match self.config.retry.next_delay(attempt, &err, retry_after) {
+                Some(delay) => thread::sleep(delay),
+                None => return Err(err),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends one request, also returning the response's `Retry-After` delay.
+    fn send<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        email_address: &str,
+    ) -> (MailboxValidatorResult<T>, Option<Duration>) {
+        if let Some(limiter) = &self.config.rate_limiter {
+            thread::sleep(limiter.reserve());
+        }
+        let res = match self.http.get(self.request_url(endpoint, email_address)).send() {
+            Ok(res) => res,
+            Err(err) => return (Err(err.into()), None),
+        };
+        let status = res.status();
+        let retry_after = retry_after(res.headers());
+        let result = res.text().map_err(Into::into).and_then(|body| parse_response(status, body));
+        (result, retry_after)
+    }
+}
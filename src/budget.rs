@@ -0,0 +1,87 @@
+//! Client-side cap on the credits a client may spend.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Number of credits a client may spend, shared by every clone of it.
+///
+/// Each request reserves one credit before it is sent; a request that
+/// fails without a result gives its credit back. Once every credit is
+/// spent, requests fail with [`MailboxValidatorError::BudgetExhausted`]
+/// without being sent, and bulk runs stop. Results served from the cache or
+/// decided locally are free. Clones share the same count, so one budget can
+/// also cap several clients.
+///
+/// [`MailboxValidatorError::BudgetExhausted`]: crate::MailboxValidatorError::BudgetExhausted
+///
+/// # Examples
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use mailboxvalidator::{Budget, MailboxValidatorClient, MailboxValidatorError};
+///
+/// let budget = Budget::new(5_000);
+/// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .budget(budget.clone())
+///     .build()?;
+/// assert_eq!(budget.remaining(), 5_000);
+///
+/// let spent = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .budget(Budget::new(0))
+///     .build()?;
+/// let err = spent.validate_email("a@example.com").unwrap_err();
+/// assert!(matches!(err, MailboxValidatorError::BudgetExhausted));
+/// # Ok::<(), MailboxValidatorError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Budget {
+    limit: u64,
+    spent: Arc<AtomicU64>,
+}
+
+impl Budget {
+    /// Creates a budget of `credits`.
+    pub fn new(credits: u64) -> Self {
+        Budget {
+            limit: credits,
+            spent: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns the credits this budget started with.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the credits spent so far, including those of requests in
+    /// flight.
+    pub fn spent(&self) -> u64 {
+        self.spent.load(Ordering::SeqCst)
+    }
+
+    /// Returns the credits left to spend.
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.spent())
+    }
+
+    /// Whether every credit has been spent.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reserves one credit, returning whether one was left.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn try_spend(&self) -> bool {
+        self.spent
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |spent| (spent < self.limit).then_some(spent + 1))
+            .is_ok()
+    }
+
+    /// Gives back a credit reserved for a request that was not charged.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn refund(&self) {
+        let _ = self
+            .spent
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |spent| spent.checked_sub(1));
+    }
+}
@@ -0,0 +1,151 @@
+//! Types shared by the bulk validation APIs.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::{MailboxValidatorResult, SingleEmailValidationRecord};
+
+/// Result of validating one address in a bulk run.
+#[derive(Debug)]
+pub struct BulkItem {
+    /// The address as given in the input.
+    pub email: String,
+    /// The validation result, or the error for this address alone.
+    pub result: MailboxValidatorResult<SingleEmailValidationRecord>,
+}
+
+/// Expected cost of a bulk run, worked out without sending any request.
+///
+/// Every address falls into exactly one of the counts after `total`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct CostEstimate {
+    /// Addresses given.
+    pub total: usize,
+    /// Addresses the checkpoint already holds.
+    pub resumed: usize,
+    /// Repeats of an earlier address that the run does not pay for again.
+    pub duplicates: usize,
+    /// Addresses answered without a request by the offline syntax check.
+    pub local: usize,
+    /// Addresses answered from the client's cache.
+    pub cached: usize,
+    /// Requests the run is expected to send, each spending a credit.
+    pub api_calls: usize,
+}
+
+/// Order in which bulk results are returned.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BulkOrder {
+    /// Same order as the input addresses.
+    Input,
+    /// As soon as each validation completes.
+    Completion,
+}
+
+/// Settings for bulk validation.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::{BulkOptions, BulkOrder};
+///
+/// let options = BulkOptions::new().concurrency(8).order(BulkOrder::Completion);
+/// assert_eq!(options.get_concurrency(), 8);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BulkOptions {
+    concurrency: usize,
+    order: BulkOrder,
+    pub(crate) pause: Option<PauseHandle>,
+}
+
+impl BulkOptions {
+    /// Four requests in flight, results in input order.
+    pub fn new() -> Self {
+        BulkOptions {
+            concurrency: 4,
+            order: BulkOrder::Input,
+            pause: None,
+        }
+    }
+
+    /// Sets how many requests may be in flight at once.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the order in which results are returned.
+    pub fn order(mut self, order: BulkOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Stops the run early once `pause` is paused.
+    pub fn pause_handle(mut self, pause: PauseHandle) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    /// Returns how many requests may be in flight at once.
+    pub fn get_concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Returns the order in which results are returned.
+    pub fn get_order(&self) -> BulkOrder {
+        self.order
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn is_paused(&self) -> bool {
+        self.pause.as_ref().is_some_and(PauseHandle::is_paused)
+    }
+}
+
+impl Default for BulkOptions {
+    fn default() -> Self {
+        BulkOptions::new()
+    }
+}
+
+/// Pauses a bulk run from another thread, e.g. a Ctrl-C handler.
+///
+/// Once paused, a run started with [`BulkOptions::pause_handle`] sends no
+/// new requests, waits for the ones in flight and returns. Run it again
+/// with the same [`Checkpoint`](crate::Checkpoint) to resume where it
+/// stopped.
+#[derive(Debug, Clone, Default)]
+pub struct PauseHandle {
+    paused: Arc<AtomicBool>,
+}
+
+impl PauseHandle {
+    /// Creates a handle that is not paused.
+    pub fn new() -> Self {
+        PauseHandle::default()
+    }
+
+    /// Asks runs using this handle to stop.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Clears the pause so the handle can be reused for the next run.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether the handle is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+impl PartialEq for PauseHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.paused, &other.paused)
+    }
+}
+
+impl Eq for PauseHandle {}
@@ -0,0 +1,222 @@
+//! Caching of validation results to avoid paying twice for one address.
+
+use std::collections::{BTreeMap, HashMap};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::records::{Record, ResultOrigin};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::{normalize, Endpoint};
+
+/// Storage for cached results.
+///
+/// Keys identify an endpoint and a normalized address; values are the
+/// results serialized as JSON. Implementations must be safe to share between
+/// threads, since every clone of a client uses the same cache. Failures of
+/// the underlying store should be treated as misses.
+///
+/// The methods are synchronous and may block. The blocking client calls
+/// them on the calling thread; the async client runs them on tokio's
+/// blocking thread pool, so they never stall its worker threads.
+pub trait Cache: Send + Sync {
+    /// Returns the value stored under `key`, unless it has expired.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores `value` under `key` for `ttl`.
+    fn put(&self, key: &str, value: String, ttl: Duration);
+}
+
+/// In-memory [`Cache`] evicting the least recently used entry once full.
+///
+/// Clones share the same entries.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use std::time::Duration;
+///
+/// use mailboxvalidator::{Cache, MailboxValidatorClient, MemoryCache};
+///
+/// let cache = MemoryCache::new(2);
+/// cache.put("a", "1".to_string(), Duration::from_secs(60));
+/// cache.put("b", "2".to_string(), Duration::from_secs(60));
+/// cache.get("a");
+/// cache.put("c", "3".to_string(), Duration::from_secs(60));
+/// assert_eq!(cache.get("b"), None);
+/// assert_eq!(cache.get("a").as_deref(), Some("1"));
+///
+/// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .cache(MemoryCache::new(10_000))
+///     .build()?;
+/// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryCache {
+    inner: Arc<Mutex<Lru>>,
+}
+
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    entries: HashMap<String, Slot>,
+    /// Keys by the tick of their last use, oldest first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Slot {
+    value: String,
+    expires: Instant,
+    used: u64,
+}
+
+impl MemoryCache {
+    /// Creates a cache holding at most `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache {
+            inner: Arc::new(Mutex::new(Lru {
+                capacity,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            })),
+        }
+    }
+
+    /// Number of entries held, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) {
+        let mut lru = self.lock();
+        lru.entries.clear();
+        lru.recency.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Lru> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let mut lru = self.lock();
+        let lru = &mut *lru;
+        let slot = lru.entries.get_mut(key)?;
+        lru.recency.remove(&slot.used);
+        if slot.expires <= Instant::now() {
+            lru.entries.remove(key);
+            return None;
+        }
+        lru.tick += 1;
+        slot.used = lru.tick;
+        lru.recency.insert(slot.used, key.to_string());
+        Some(slot.value.clone())
+    }
+
+    fn put(&self, key: &str, value: String, ttl: Duration) {
+        let mut lru = self.lock();
+        if lru.capacity == 0 {
+            return;
+        }
+        lru.tick += 1;
+        let slot = Slot {
+            value,
+            expires: Instant::now() + ttl,
+            used: lru.tick,
+        };
+        lru.recency.insert(slot.used, key.to_string());
+        if let Some(old) = lru.entries.insert(key.to_string(), slot) {
+            lru.recency.remove(&old.used);
+        }
+        while lru.entries.len() > lru.capacity {
+            let Some((_, oldest)) = lru.recency.pop_first() else {
+                break;
+            };
+            lru.entries.remove(&oldest);
+        }
+    }
+}
+
+/// A [`Cache`] together with how long each endpoint's results stay fresh.
+#[cfg(any(feature = "blocking", feature = "async"))]
+#[derive(Clone)]
+pub(crate) struct ResultCache {
+    store: Arc<dyn Cache>,
+    ttls: HashMap<Endpoint, Duration>,
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl ResultCache {
+    pub(crate) fn new(store: Arc<dyn Cache>, ttls: HashMap<Endpoint, Duration>) -> Self {
+        ResultCache { store, ttls }
+    }
+
+    pub(crate) fn with_ttls(&self, ttls: HashMap<Endpoint, Duration>) -> Self {
+        ResultCache::new(self.store.clone(), ttls)
+    }
+
+    /// Returns the cached result for `email_address`, marked as a cache hit.
+    ///
+    /// The result may have been stored for another address of the same
+    /// mailbox, so it is relabelled with `email_address`.
+    pub(crate) fn get<T: Record>(&self, endpoint: Endpoint, email_address: &str) -> Option<T> {
+        let value = self.store.get(&key(endpoint, email_address))?;
+        let mut record: T = serde_json::from_str(&value).ok()?;
+        record.set_origin(ResultOrigin::Cache);
+        record.set_email_address(email_address);
+        Some(record)
+    }
+
+    /// Stores `record` if it is worth keeping.
+    pub(crate) fn put<T: Record>(&self, endpoint: Endpoint, email_address: &str, record: &T) {
+        let ttl = self.ttl(endpoint);
+        if ttl.is_zero() || !record.is_cacheable() {
+            return;
+        }
+        if let Ok(value) = serde_json::to_string(record) {
+            self.store.put(&key(endpoint, email_address), value, ttl);
+        }
+    }
+
+    fn ttl(&self, endpoint: Endpoint) -> Duration {
+        self.ttls.get(&endpoint).copied().unwrap_or_else(|| default_ttl(endpoint))
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl fmt::Debug for ResultCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultCache").field("ttls", &self.ttls).finish_non_exhaustive()
+    }
+}
+
+/// How long results stay fresh unless configured otherwise. Mailboxes come
+/// and go, so single validations expire sooner than provider lookups.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn default_ttl(endpoint: Endpoint) -> Duration {
+    const DAY: u64 = 24 * 60 * 60;
+    match endpoint {
+        Endpoint::Single => Duration::from_secs(7 * DAY),
+        Endpoint::Disposable | Endpoint::Free => Duration::from_secs(30 * DAY),
+    }
+}
+
+/// Cache key for `email_address` on `endpoint`. Addresses of the same
+/// mailbox share a key.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn key(endpoint: Endpoint, email_address: &str) -> String {
+    format!("{}:{}", endpoint.path(), normalize(email_address))
+}
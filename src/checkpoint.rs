@@ -0,0 +1,201 @@
+//! On-disk checkpoints that let bulk validation resume after a crash.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BulkItem, SingleEmailValidationRecord};
+
+/// One line of the checkpoint file.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    email: String,
+    record: SingleEmailValidationRecord,
+}
+
+/// File of already validated addresses and their results.
+///
+/// Each result is appended as one JSON line as soon as it arrives, so a
+/// restarted job skips everything that was paid for before it stopped. A
+/// line cut short by a crash is dropped when the file is reopened.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::{Checkpoint, SingleEmailValidationRecord};
+///
+/// let path = std::env::temp_dir().join("mbv-checkpoint-doctest.jsonl");
+/// # let _ = std::fs::remove_file(&path);
+/// let record: SingleEmailValidationRecord = serde_json::from_str(r#"{
+///     "email_address": "a@example.com", "base_email_address": "a@example.com",
+///     "domain": "example.com", "is_free": false, "is_syntax": true, "is_domain": true,
+///     "is_smtp": true, "is_verified": true, "is_server_down": false, "is_greylisted": false,
+///     "is_disposable": false, "is_suppressed": false, "is_role": false, "is_high_risk": false,
+///     "is_catchall": false, "is_dmarc_enforced": false, "is_strict_spf": false,
+///     "website_exist": true, "status": true, "mailboxvalidator_score": 0.9,
+///     "time_taken": 0.2, "credits_available": 100
+/// }"#)?;
+///
+/// let mut checkpoint = Checkpoint::open(&path)?;
+/// checkpoint.record("a@example.com", record.clone())?;
+/// drop(checkpoint);
+///
+/// let checkpoint = Checkpoint::open(&path)?;
+/// assert_eq!(checkpoint.len(), 1);
+/// assert_eq!(checkpoint.get("a@example.com"), Some(&record));
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    file: File,
+    done: HashMap<String, SingleEmailValidationRecord>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint at `path`, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be read or created.
+    /// * Error when a complete line of the file is not a checkpoint entry.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        // Drop a trailing line left incomplete by a crash.
+        let complete = contents.rfind('\n').map_or(0, |end| end + 1);
+        if complete < contents.len() {
+            file.set_len(complete as u64)?;
+        }
+
+        let mut done = HashMap::new();
+        for line in contents[..complete]
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+        {
+            let entry: Entry = serde_json::from_str(line)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            done.insert(entry.email, entry.record);
+        }
+
+        Ok(Checkpoint { path, file, done })
+    }
+
+    /// Returns the stored result for `email`, if it was validated before.
+    pub fn get(&self, email: &str) -> Option<&SingleEmailValidationRecord> {
+        self.done.get(email)
+    }
+
+    /// Whether `email` was validated before.
+    pub fn contains(&self, email: &str) -> bool {
+        self.done.contains_key(email)
+    }
+
+    /// Number of stored results.
+    pub fn len(&self) -> usize {
+        self.done.len()
+    }
+
+    /// Whether no results are stored.
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty()
+    }
+
+    /// Iterates over the stored addresses and results, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SingleEmailValidationRecord)> {
+        self.done
+            .iter()
+            .map(|(email, record)| (email.as_str(), record))
+    }
+
+    /// Appends the result for `email` to the file.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be written.
+    pub fn record(&mut self, email: &str, record: SingleEmailValidationRecord) -> io::Result<()> {
+        let entry = Entry {
+            email: email.to_string(),
+            record,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.done.insert(entry.email, entry.record);
+        Ok(())
+    }
+
+    /// Flushes the file to disk.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be synced.
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// Rewrites the file with one line per address.
+    ///
+    /// The new file is written next to the old one and then renamed over it,
+    /// so the checkpoint is never left half written.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be written or replaced.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut contents = String::new();
+        for (email, record) in &self.done {
+            let entry = Entry {
+                email: email.clone(),
+                record: record.clone(),
+            };
+            contents.push_str(&serde_json::to_string(&entry)?);
+            contents.push('\n');
+        }
+        write_atomic(&self.path, contents.as_bytes())?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Outcome of a resumable bulk run.
+///
+/// Successful results are stored in the [`Checkpoint`].
+#[derive(Debug, Default)]
+pub struct JobReport {
+    /// Addresses skipped because the checkpoint already held their result.
+    pub skipped: usize,
+    /// Addresses validated and added to the checkpoint during this run.
+    pub validated: usize,
+    /// Addresses that failed during this run; they are retried next time.
+    pub failures: Vec<BulkItem>,
+    /// Whether the run stopped early because it was paused or the client's
+    /// budget was spent.
+    pub paused: bool,
+}
+
+/// Writes `contents` to `path` through a temporary file and a rename, so
+/// readers see either the old or the new file, never a partial one.
+fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    let temp = path.with_file_name(file_name);
+
+    let mut file = File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp, path)
+}
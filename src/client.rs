@@ -0,0 +1,553 @@
+//! Client configuration and the request/response handling shared by the
+//! blocking and async clients.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Proxy;
+use reqwest::StatusCode;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+
+#[cfg(feature = "async")]
+use crate::AsyncMailboxValidatorClient;
+#[cfg(feature = "blocking")]
+use crate::MailboxValidatorClient;
+use crate::cache::ResultCache;
+use crate::credits::{CreditTracker, LowBalanceAlert};
+use crate::idn;
+use crate::records::Record;
+use crate::syntax;
+use crate::{
+    normalize, ApiKey, Budget, Cache, CheckMode, Checkpoint, CostEstimate, DisposableEmailRecord, DomainList,
+    ErrorRecord, FreeEmailRecord, MailboxValidatorError, MailboxValidatorResult, RateLimiter, RetryPolicy,
+    SingleEmailValidationRecord, TypoSuggester,
+};
+
+/// Base URL of the MailboxValidator v2 API.
+pub const DEFAULT_BASE_URL: &str = "https://api.mailboxvalidator.com/v2/";
+
+/// Value sent as the `source` query parameter unless overridden.
+pub const DEFAULT_SOURCE: &str = "sdk-rust-mbv";
+
+/// Builder for the blocking and async clients.
+///
+/// Obtained from `MailboxValidatorClient::builder` and finished with
+/// `build()`, or from `AsyncMailboxValidatorClient::builder` and finished
+/// with `build_async()`.
+#[derive(Debug)]
+pub struct MailboxValidatorClientBuilder {
+    api_key: ApiKey,
+    base_url: String,
+    source: String,
+    retry: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<ResultCache>,
+    cache_ttls: HashMap<Endpoint, Duration>,
+    check_syntax: bool,
+    disposable_check: CheckMode,
+    disposable_domains: Option<DomainList>,
+    free_check: CheckMode,
+    free_domains: Option<DomainList>,
+    suggest_typos: bool,
+    typo_suggester: Option<TypoSuggester>,
+    low_balance_alert: Option<LowBalanceAlert>,
+    budget: Option<Budget>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<Proxy>,
+}
+
+impl MailboxValidatorClientBuilder {
+    pub(crate) fn new(api_key: ApiKey) -> Self {
+        MailboxValidatorClientBuilder {
+            api_key,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            source: DEFAULT_SOURCE.to_string(),
+            retry: RetryPolicy::none(),
+            rate_limiter: None,
+            cache: None,
+            cache_ttls: HashMap::new(),
+            check_syntax: false,
+            disposable_check: CheckMode::Remote,
+            disposable_domains: None,
+            free_check: CheckMode::Remote,
+            free_domains: None,
+            suggest_typos: false,
+            typo_suggester: None,
+            low_balance_alert: None,
+            budget: None,
+            timeout: None,
+            user_agent: None,
+            proxy: None,
+        }
+    }
+
+    /// Overrides the API base URL, e.g. to point at a mock server.
+    ///
+    /// The endpoint paths are joined to it, so it must be a URL such as
+    /// `http://127.0.0.1:8080/v2/`; otherwise building the client fails
+    /// with [`MailboxValidatorError::InvalidBaseUrl`].
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(feature = "blocking", doc = "```")]
+    #[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+    /// use mailboxvalidator::{MailboxValidatorClient, MailboxValidatorError};
+    ///
+    /// let result = MailboxValidatorClient::builder("YOUR_API_KEY").base_url("mailto:x").build();
+    /// assert!(matches!(result, Err(MailboxValidatorError::InvalidBaseUrl(_))));
+    /// ```
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        self.base_url = base_url;
+        self
+    }
+
+    /// Sets a total timeout for each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with each request.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Overrides the `source` query parameter reported to the API.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Retries transient failures according to `policy`.
+    ///
+    /// By default requests are not retried.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Limits the request rate with `limiter`, which every clone of the
+    /// client shares.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Serves repeated lookups from `cache` instead of spending credits.
+    ///
+    /// Results are stored per endpoint under the [`normalize`](crate::normalize)d
+    /// address, so `j.doe+news@gmail.com` is answered from the result for
+    /// `jdoe@gmail.com`, and marked with [`ResultOrigin::Cache`](crate::ResultOrigin) when
+    /// served from the cache. Errors and results the API could not decide
+    /// are not cached. Every clone of the client shares the cache.
+    pub fn cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(ResultCache::new(Arc::new(cache), HashMap::new()));
+        self
+    }
+
+    /// Sets how long cached results of `endpoint` stay fresh.
+    ///
+    /// Defaults to 7 days for [`Endpoint::Single`] and 30 days for the
+    /// others. A zero `ttl` disables caching for the endpoint.
+    pub fn cache_ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.cache_ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// Checks each address with [`syntax::parse`](crate::syntax::parse)
+    /// before sending it, so malformed input costs no request.
+    ///
+    /// A rejected address validates to a record with `is_syntax` and
+    /// `status` set to `false` and [`ResultOrigin::Local`](crate::ResultOrigin);
+    /// the disposable and free checks fail with
+    /// [`MailboxValidatorError::InvalidSyntax`]. Off by default.
+    pub fn check_syntax(mut self, check_syntax: bool) -> Self {
+        self.check_syntax = check_syntax;
+        self
+    }
+
+    /// Sets how `is_disposable_email` is answered.
+    ///
+    /// With [`CheckMode::Local`] or [`CheckMode::PreFilter`], addresses on the
+    /// disposable domain list are reported as disposable without a request,
+    /// with [`ResultOrigin::Local`](crate::ResultOrigin) and no credits.
+    /// Defaults to [`CheckMode::Remote`].
+    pub fn disposable_check(mut self, mode: CheckMode) -> Self {
+        self.disposable_check = mode;
+        self
+    }
+
+    /// Replaces the bundled [`DomainList::disposable`] used for local
+    /// disposable checks.
+    pub fn disposable_domains(mut self, domains: DomainList) -> Self {
+        self.disposable_domains = Some(domains);
+        self
+    }
+
+    /// Sets how `is_free_email` is answered, as
+    /// [`disposable_check`](Self::disposable_check) does for disposable checks.
+    ///
+    /// Defaults to [`CheckMode::Remote`].
+    pub fn free_check(mut self, mode: CheckMode) -> Self {
+        self.free_check = mode;
+        self
+    }
+
+    /// Replaces the bundled [`DomainList::free`] used for local free email
+    /// checks.
+    pub fn free_domains(mut self, domains: DomainList) -> Self {
+        self.free_domains = Some(domains);
+        self
+    }
+
+    /// Attaches a "did you mean" correction to validations that fail, e.g.
+    /// `jane@gmail.com` for `jane@gmial.com`.
+    ///
+    /// The correction is computed offline by a [`TypoSuggester`] and stored
+    /// in the record's `suggestion`, which stays `None` for valid addresses.
+    /// Off by default.
+    pub fn suggest_typos(mut self, suggest_typos: bool) -> Self {
+        self.suggest_typos = suggest_typos;
+        self
+    }
+
+    /// Replaces the bundled [`TypoSuggester::new`] used for typo
+    /// suggestions.
+    pub fn typo_suggester(mut self, suggester: TypoSuggester) -> Self {
+        self.typo_suggester = Some(suggester);
+        self
+    }
+
+    /// Runs `callback` with the balance when an API response reports fewer
+    /// than `threshold` credits left, e.g. to warn before requests start
+    /// failing with error 10004.
+    ///
+    /// The callback runs once each time the balance drops from at least
+    /// `threshold` to below it, including on the first response, and runs
+    /// on the thread or task that made the request. Results served from the
+    /// cache or decided locally carry no balance and never trigger it, and
+    /// neither does an older, higher balance from a concurrent request that
+    /// is answered after a newer one.
+    pub fn low_balance_alert(mut self, threshold: i64, callback: impl Fn(i64) + Send + Sync + 'static) -> Self {
+        self.low_balance_alert = Some(LowBalanceAlert {
+            threshold,
+            callback: Arc::new(callback),
+        });
+        self
+    }
+
+    /// Caps the credits the client may spend at `budget`, which every clone
+    /// of the client shares.
+    ///
+    /// Once it is spent, requests fail with
+    /// [`MailboxValidatorError::BudgetExhausted`] without being sent, and
+    /// bulk runs stop. By default spending is not capped.
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Routes all requests through the given proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Builds a blocking client.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the base URL cannot be parsed or cannot be a base for
+    ///   the endpoint paths.
+    /// * Error when the underlying HTTP client cannot be initialised.
+    #[cfg(feature = "blocking")]
+    pub fn build(self) -> MailboxValidatorResult<MailboxValidatorClient> {
+        let config = self.config()?;
+        let mut http = reqwest::blocking::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+
+        Ok(MailboxValidatorClient::from_parts(http.build()?, config))
+    }
+
+    /// Builds an async client.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the base URL cannot be parsed or cannot be a base for
+    ///   the endpoint paths.
+    /// * Error when the underlying HTTP client cannot be initialised.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> MailboxValidatorResult<AsyncMailboxValidatorClient> {
+        let config = self.config()?;
+        let mut http = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+
+        Ok(AsyncMailboxValidatorClient::from_parts(http.build()?, config))
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn config(&self) -> MailboxValidatorResult<ClientConfig> {
+        let base_url = Url::parse(&self.base_url).map_err(MailboxValidatorError::InvalidBaseUrl)?;
+        Ok(ClientConfig {
+            api_key: self.api_key.clone(),
+            endpoint_urls: EndpointUrls::new(&base_url).map_err(MailboxValidatorError::InvalidBaseUrl)?,
+            source: self.source.clone(),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            cache: self.cache.as_ref().map(|cache| cache.with_ttls(self.cache_ttls.clone())),
+            check_syntax: self.check_syntax,
+            disposable_check: self.disposable_check,
+            disposable_domains: local_list(&self.disposable_domains, self.disposable_check, DomainList::disposable),
+            free_check: self.free_check,
+            free_domains: local_list(&self.free_domains, self.free_check, DomainList::free),
+            typo_suggester: self
+                .suggest_typos
+                .then(|| Arc::new(self.typo_suggester.clone().unwrap_or_default())),
+            credits: CreditTracker::new(self.low_balance_alert.clone()),
+            budget: self.budget.clone(),
+        })
+    }
+}
+
+/// Settings shared by the blocking and async clients.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientConfig {
+    api_key: ApiKey,
+    endpoint_urls: EndpointUrls,
+    source: String,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) cache: Option<ResultCache>,
+    check_syntax: bool,
+    disposable_check: CheckMode,
+    disposable_domains: Arc<DomainList>,
+    free_check: CheckMode,
+    free_domains: Arc<DomainList>,
+    typo_suggester: Option<Arc<TypoSuggester>>,
+    pub(crate) credits: CreditTracker,
+    budget: Option<Budget>,
+}
+
+impl ClientConfig {
+    /// Returns the local answer for `email_address` if the offline syntax
+    /// check is enabled and rejects it.
+    pub(crate) fn check_syntax<T: Record>(&self, email_address: &str) -> Option<MailboxValidatorResult<T>> {
+        if !self.check_syntax {
+            return None;
+        }
+        let err = syntax::parse(email_address).err()?;
+        Some(T::invalid_syntax(email_address).ok_or(MailboxValidatorError::InvalidSyntax(err)))
+    }
+
+    /// Returns the local answer to a disposable check, if the configured
+    /// [`CheckMode`] allows one.
+    pub(crate) fn local_disposable(&self, email_address: &str) -> Option<MailboxValidatorResult<DisposableEmailRecord>> {
+        if self.disposable_check == CheckMode::Remote {
+            return None;
+        }
+        if let Some(result) = self.check_syntax(email_address) {
+            return Some(result);
+        }
+        let record = self.disposable_domains.check_disposable(email_address);
+        if record.is_disposable != Some(true) && self.disposable_check == CheckMode::PreFilter {
+            return None;
+        }
+        Some(Ok(record))
+    }
+
+    /// Returns the local answer to a free email check, if the configured
+    /// [`CheckMode`] allows one.
+    pub(crate) fn local_free(&self, email_address: &str) -> Option<MailboxValidatorResult<FreeEmailRecord>> {
+        if self.free_check == CheckMode::Remote {
+            return None;
+        }
+        if let Some(result) = self.check_syntax(email_address) {
+            return Some(result);
+        }
+        let record = self.free_domains.check_free(email_address);
+        if record.is_free != Some(true) && self.free_check == CheckMode::PreFilter {
+            return None;
+        }
+        Some(Ok(record))
+    }
+
+    /// Attaches a typo suggestion for `email_address` to its `record` if
+    /// suggestions are enabled and the address is not valid.
+    pub(crate) fn suggest(&self, email_address: &str, mut record: SingleEmailValidationRecord) -> SingleEmailValidationRecord {
+        if let Some(suggester) = &self.typo_suggester {
+            if record.status != Some(true) {
+                record.suggestion = suggester.suggest(email_address);
+            }
+        }
+        record
+    }
+
+    /// Reserves a credit of the budget, if any, for the next request, and
+    /// returns the request's ticket for [`ClientConfig::settle`].
+    pub(crate) fn reserve_credit(&self) -> MailboxValidatorResult<u64> {
+        match &self.budget {
+            Some(budget) if !budget.try_spend() => Err(MailboxValidatorError::BudgetExhausted),
+            _ => Ok(self.credits.start()),
+        }
+    }
+
+    /// Accounts for the response to a request: records the reported balance
+    /// and gives the reserved credit back if the request failed.
+    pub(crate) fn settle<T: Record>(&self, ticket: u64, result: &MailboxValidatorResult<T>) {
+        self.credits.observe(ticket, result);
+        if let (Err(_), Some(budget)) = (result, &self.budget) {
+            budget.refund();
+        }
+    }
+
+    /// Works out how many of `emails` a bulk validation would send to the
+    /// API, see [`CostEstimate`].
+    ///
+    /// Duplicates are free when a checkpoint skips exact repeats, or when
+    /// the cache answers addresses of a mailbox validated earlier in the run.
+    pub(crate) fn estimate<I, S>(&self, emails: I, checkpoint: Option<&Checkpoint>) -> CostEstimate
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut estimate = CostEstimate::default();
+        let mut seen = HashSet::new();
+        let mut seen_mailboxes = HashSet::new();
+        for email in emails {
+            let email: String = email.into();
+            estimate.total += 1;
+            if let Some(checkpoint) = checkpoint {
+                if checkpoint.contains(&email) {
+                    estimate.resumed += 1;
+                    continue;
+                }
+                if !seen.insert(email.clone()) {
+                    estimate.duplicates += 1;
+                    continue;
+                }
+            }
+            if self.cache.is_some() && !seen_mailboxes.insert(normalize(&email)) {
+                estimate.duplicates += 1;
+            } else if self.check_syntax::<SingleEmailValidationRecord>(&email).is_some() {
+                estimate.local += 1;
+            } else if self
+                .cache
+                .as_ref()
+                .is_some_and(|cache| cache.get::<SingleEmailValidationRecord>(Endpoint::Single, &email).is_some())
+            {
+                estimate.cached += 1;
+            } else {
+                estimate.api_calls += 1;
+            }
+        }
+        estimate
+    }
+
+    /// Builds the request URL, percent-encoding every query parameter.
+    pub(crate) fn url(&self, endpoint: Endpoint, email_address: &str) -> Url {
+        let mut url = self.endpoint_urls.get(endpoint).clone();
+        url.query_pairs_mut()
+            .append_pair("email", &idn::email_to_ascii(email_address))
+            .append_pair("key", self.api_key.expose_secret())
+            .append_pair("format", "json")
+            .append_pair("source", &self.source);
+        url
+    }
+}
+
+/// The list for a local check: the configured one, else the bundled one if
+/// `mode` needs it.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn local_list(domains: &Option<DomainList>, mode: CheckMode, bundled: fn() -> DomainList) -> Arc<DomainList> {
+    Arc::new(match (domains, mode) {
+        (Some(domains), _) => domains.clone(),
+        (None, CheckMode::Remote) => DomainList::new(),
+        (None, _) => bundled(),
+    })
+}
+
+/// The URL of each endpoint, joined to the base URL once when the client is
+/// built.
+#[derive(Debug, Clone)]
+struct EndpointUrls {
+    single: Url,
+    disposable: Url,
+    free: Url,
+}
+
+impl EndpointUrls {
+    fn new(base_url: &Url) -> Result<Self, url::ParseError> {
+        Ok(EndpointUrls {
+            single: base_url.join(Endpoint::Single.path())?,
+            disposable: base_url.join(Endpoint::Disposable.path())?,
+            free: base_url.join(Endpoint::Free.path())?,
+        })
+    }
+
+    fn get(&self, endpoint: Endpoint) -> &Url {
+        match endpoint {
+            Endpoint::Single => &self.single,
+            Endpoint::Disposable => &self.disposable,
+            Endpoint::Free => &self.free,
+        }
+    }
+}
+
+/// MailboxValidator API endpoints.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Endpoint {
+    /// Single Validation API.
+    Single,
+    /// Disposable Email API.
+    Disposable,
+    /// Free Email API.
+    Free,
+}
+
+impl Endpoint {
+    pub(crate) fn path(self) -> &'static str {
+        match self {
+            Endpoint::Single => "validation/single",
+            Endpoint::Disposable => "email/disposable",
+            Endpoint::Free => "email/free",
+        }
+    }
+}
+
+/// Turns a response status and body into a record or an error.
+pub(crate) fn parse_response<T>(status: StatusCode, body: String) -> MailboxValidatorResult<T>
+where
+    T: DeserializeOwned,
+{
+    if status == StatusCode::OK {
+        Ok(serde_json::from_str(&body)?)
+    } else if let Ok(parsed) = serde_json::from_str::<ErrorRecord>(&body) {
+        Err(MailboxValidatorError::Api(parsed.error))
+    } else {
+        Err(MailboxValidatorError::UnexpectedStatus { status, body })
+    }
+}
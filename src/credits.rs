@@ -0,0 +1,202 @@
+//! Tracking of the account's credit balance.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::records::Record;
+use crate::MailboxValidatorResult;
+
+/// Callback run with the balance when it drops below `threshold`.
+#[derive(Clone)]
+pub(crate) struct LowBalanceAlert {
+    pub(crate) threshold: i64,
+    pub(crate) callback: Arc<dyn Fn(i64) + Send + Sync>,
+}
+
+impl fmt::Debug for LowBalanceAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LowBalanceAlert").field("threshold", &self.threshold).finish_non_exhaustive()
+    }
+}
+
+/// The balance reported by the latest API response, shared by every clone
+/// of a client.
+#[derive(Debug, Clone)]
+pub(crate) struct CreditTracker {
+    balance: Arc<Mutex<Balance>>,
+    alert: Option<LowBalanceAlert>,
+}
+
+#[derive(Debug, Default)]
+struct Balance {
+    remaining: Option<i64>,
+    /// Number of requests started so far, handing out their tickets.
+    started: u64,
+    /// Ticket of the newest request whose response was recorded.
+    newest: u64,
+}
+
+impl CreditTracker {
+    pub(crate) fn new(alert: Option<LowBalanceAlert>) -> Self {
+        CreditTracker {
+            balance: Arc::new(Mutex::new(Balance::default())),
+            alert,
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> Option<i64> {
+        self.balance.lock().unwrap_or_else(PoisonError::into_inner).remaining
+    }
+
+    /// Returns the ticket of a request about to be sent, which orders its
+    /// response among those of concurrent requests.
+    pub(crate) fn start(&self) -> u64 {
+        let mut balance = self.balance.lock().unwrap_or_else(PoisonError::into_inner);
+        balance.started += 1;
+        balance.started
+    }
+
+    /// Records the balance reported by the response to the request holding
+    /// `ticket`. Running out of credits counts as a balance of 0.
+    pub(crate) fn observe<T: Record>(&self, ticket: u64, result: &MailboxValidatorResult<T>) {
+        match result {
+            Ok(record) => self.update(ticket, record.credits_available()),
+            Err(err) if err.api_error_code().is_some_and(|code| code.is_billing_problem()) => self.update(ticket, 0),
+            Err(_) => {}
+        }
+    }
+
+    /// Stores `credits` and runs the low-balance callback if the balance
+    /// just dropped below the threshold.
+    ///
+    /// Concurrent responses settle in completion order, so a request sent
+    /// before the newest recorded one may report an older, higher balance.
+    /// That balance is ignored, so that it neither hides the newer one nor
+    /// re-arms the callback.
+    fn update(&self, ticket: u64, credits: i64) {
+        let previous = {
+            let mut balance = self.balance.lock().unwrap_or_else(PoisonError::into_inner);
+            if ticket < balance.newest && balance.remaining.is_some_and(|remaining| credits > remaining) {
+                return;
+            }
+            balance.newest = balance.newest.max(ticket);
+            balance.remaining.replace(credits)
+        };
+        if let Some(alert) = &self.alert {
+            if credits < alert.threshold && previous.is_none_or(|previous| previous >= alert.threshold) {
+                (alert.callback)(credits);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{CreditTracker, LowBalanceAlert};
+    use crate::{ApiErrorCode, ErrorRecord1, FreeEmailRecord, MailboxValidatorError, MailboxValidatorResult, ResultOrigin};
+
+    /// A tracker alerting below 100, and the balances it alerted with.
+    fn alerting_tracker() -> (CreditTracker, Arc<Mutex<Vec<i64>>>) {
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let seen = alerts.clone();
+        let alert = LowBalanceAlert {
+            threshold: 100,
+            callback: Arc::new(move |credits| seen.lock().unwrap().push(credits)),
+        };
+        (CreditTracker::new(Some(alert)), alerts)
+    }
+
+    fn response(credits: i64) -> MailboxValidatorResult<FreeEmailRecord> {
+        Ok(FreeEmailRecord {
+            email_address: "jane@example.com".to_string(),
+            is_free: Some(false),
+            credits_available: credits,
+            origin: ResultOrigin::Api,
+        })
+    }
+
+    /// Starts and settles one request at a time.
+    fn observe(tracker: &CreditTracker, credits: i64) {
+        let ticket = tracker.start();
+        tracker.observe(ticket, &response(credits));
+    }
+
+    #[test]
+    fn fires_on_the_first_response_below_the_threshold() {
+        let (tracker, alerts) = alerting_tracker();
+        assert_eq!(tracker.remaining(), None);
+        observe(&tracker, 101);
+        observe(&tracker, 100);
+        assert!(alerts.lock().unwrap().is_empty());
+        observe(&tracker, 99);
+        assert_eq!(*alerts.lock().unwrap(), [99]);
+        assert_eq!(tracker.remaining(), Some(99));
+
+        // Already below the threshold when the first response arrives.
+        let (tracker, alerts) = alerting_tracker();
+        observe(&tracker, 5);
+        assert_eq!(*alerts.lock().unwrap(), [5]);
+    }
+
+    #[test]
+    fn does_not_fire_again_while_below_the_threshold() {
+        let (tracker, alerts) = alerting_tracker();
+        for credits in [99, 98, 50, 0] {
+            observe(&tracker, credits);
+        }
+        assert_eq!(*alerts.lock().unwrap(), [99]);
+    }
+
+    #[test]
+    fn fires_again_after_a_top_up() {
+        let (tracker, alerts) = alerting_tracker();
+        for credits in [99, 98, 1_000, 999, 99] {
+            observe(&tracker, credits);
+        }
+        assert_eq!(*alerts.lock().unwrap(), [99, 99]);
+        assert_eq!(tracker.remaining(), Some(99));
+    }
+
+    #[test]
+    fn insufficient_credits_sets_the_balance_to_zero() {
+        let (tracker, alerts) = alerting_tracker();
+        observe(&tracker, 500);
+        let error = MailboxValidatorError::Api(ErrorRecord1 {
+            error_code: ApiErrorCode::InsufficientCredits,
+            error_message: "Insufficient credits.".to_string(),
+        });
+        tracker.observe::<FreeEmailRecord>(tracker.start(), &Err(error));
+        assert_eq!(tracker.remaining(), Some(0));
+        assert_eq!(*alerts.lock().unwrap(), [0]);
+
+        // Other errors report no balance.
+        let error = MailboxValidatorError::Api(ErrorRecord1 {
+            error_code: ApiErrorCode::Unknown,
+            error_message: "Unknown error.".to_string(),
+        });
+        tracker.observe::<FreeEmailRecord>(tracker.start(), &Err(error));
+        assert_eq!(tracker.remaining(), Some(0));
+    }
+
+    #[test]
+    fn ignores_older_higher_balances_settling_late() {
+        let (tracker, alerts) = alerting_tracker();
+        observe(&tracker, 102);
+        // Three requests in flight at once, settling newest first.
+        let tickets = [tracker.start(), tracker.start(), tracker.start()];
+        tracker.observe(tickets[2], &response(99));
+        tracker.observe(tickets[0], &response(101));
+        tracker.observe(tickets[1], &response(100));
+        assert_eq!(tracker.remaining(), Some(99));
+        observe(&tracker, 98);
+        assert_eq!(*alerts.lock().unwrap(), [99]);
+
+        // An older response may still lower the balance.
+        let tickets = [tracker.start(), tracker.start()];
+        tracker.observe(tickets[1], &response(97));
+        tracker.observe(tickets[0], &response(96));
+        assert_eq!(tracker.remaining(), Some(96));
+    }
+}
@@ -0,0 +1,185 @@
+//! Domain lists for answering provider checks offline.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{idn, DisposableEmailRecord, FreeEmailRecord, ResultOrigin};
+
+/// Disposable email domains shipped with the crate.
+const DISPOSABLE_DOMAINS: &str = include_str!("../data/disposable_domains.txt");
+
+/// Free email provider domains shipped with the crate.
+const FREE_DOMAINS: &str = include_str!("../data/free_domains.txt");
+
+/// How a client answers a provider check.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum CheckMode {
+    /// Always ask the MailboxValidator API.
+    #[default]
+    Remote,
+    /// Answer from the local domain list only, without any request.
+    Local,
+    /// Answer domains on the local list locally and ask the API about the rest.
+    PreFilter,
+}
+
+/// Set of email domains, such as disposable email providers.
+///
+/// Domains are matched case-insensitively and in either their Unicode or
+/// punycode form, and a listed domain also matches its subdomains.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::DomainList;
+///
+/// let mut domains = DomainList::disposable();
+/// assert!(domains.contains("mailinator.com"));
+/// assert!(domains.contains_email("someone@eu.Mailinator.com"));
+/// assert!(!domains.contains("example.com"));
+///
+/// domains.extend(DomainList::parse("# our own additions\nthrowaway.example\n"));
+/// assert!(domains.contains_email("someone@throwaway.example"));
+/// domains.insert("bücher.example");
+/// assert!(domains.contains("xn--bcher-kva.example"));
+///
+/// let mut free = DomainList::free();
+/// free.insert("mail.example");
+/// let record = free.check_free("someone@mail.example");
+/// assert_eq!(record.is_free, Some(true));
+/// assert_eq!(free.check_free("someone@example.com").is_free, Some(false));
+/// assert_eq!(free.check_free("someone@yahoo.ca").is_free, Some(true));
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct DomainList {
+    domains: HashSet<String>,
+}
+
+impl DomainList {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        DomainList::default()
+    }
+
+    /// The disposable email domains bundled with this version of the crate.
+    pub fn disposable() -> Self {
+        DomainList::parse(DISPOSABLE_DOMAINS)
+    }
+
+    /// The free email provider domains bundled with this version of the
+    /// crate, covering the major webmail providers with their regional
+    /// domains, and regional providers.
+    pub fn free() -> Self {
+        DomainList::parse(FREE_DOMAINS)
+    }
+
+    /// Reads a list with one domain per line. Blank lines and lines
+    /// starting with `#` are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut list = DomainList::new();
+        for line in text.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                list.insert(line);
+            }
+        }
+        list
+    }
+
+    /// Reads a list from a file in the format of [`DomainList::parse`].
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be read or is not UTF-8.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(DomainList::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Adds `domain` to the list.
+    pub fn insert(&mut self, domain: &str) {
+        self.domains.insert(normalize(domain));
+    }
+
+    /// Removes `domain` from the list, returning whether it was listed.
+    pub fn remove(&mut self, domain: &str) -> bool {
+        self.domains.remove(&normalize(domain))
+    }
+
+    /// Adds every domain of `other`.
+    pub fn extend(&mut self, other: DomainList) {
+        self.domains.extend(other.domains);
+    }
+
+    /// Number of listed domains.
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Whether no domain is listed.
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Whether `domain` or one of its parent domains is listed.
+    pub fn contains(&self, domain: &str) -> bool {
+        let domain = normalize(domain);
+        let mut rest = domain.as_str();
+        loop {
+            if self.domains.contains(rest) {
+                return true;
+            }
+            match rest.split_once('.') {
+                Some((_, parent)) => rest = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Whether the domain of `email_address` is listed.
+    pub fn contains_email(&self, email_address: &str) -> bool {
+        email_domain(email_address).is_some_and(|domain| self.contains(domain))
+    }
+
+    /// Answers a disposable check from this list, without a request.
+    ///
+    /// The record has [`ResultOrigin::Local`] and no credits, and
+    /// `is_disposable` is `None` if the address has no domain.
+    pub fn check_disposable(&self, email_address: &str) -> DisposableEmailRecord {
+        DisposableEmailRecord {
+            email_address: email_address.to_string(),
+            is_disposable: email_domain(email_address).map(|domain| self.contains(domain)),
+            credits_available: 0,
+            origin: ResultOrigin::Local,
+        }
+    }
+
+    /// Answers a free email check from this list, without a request.
+    ///
+    /// The record has [`ResultOrigin::Local`] and no credits, and `is_free`
+    /// is `None` if the address has no domain.
+    pub fn check_free(&self, email_address: &str) -> FreeEmailRecord {
+        FreeEmailRecord {
+            email_address: email_address.to_string(),
+            is_free: email_domain(email_address).map(|domain| self.contains(domain)),
+            credits_available: 0,
+            origin: ResultOrigin::Local,
+        }
+    }
+}
+
+/// Returns the part of `email_address` after its last `@`.
+fn email_domain(email_address: &str) -> Option<&str> {
+    email_address
+        .trim()
+        .rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Lowercases `domain` and converts it to ASCII, so Unicode and punycode
+/// spellings of a domain match.
+fn normalize(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.');
+    idn::domain_to_ascii(domain).unwrap_or_else(|| domain.to_lowercase())
+}
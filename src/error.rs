@@ -0,0 +1,225 @@
+//! Error type returned by every fallible operation in this crate.
+
+use std::error::Error;
+use std::fmt;
+
+use reqwest::StatusCode;
+
+use crate::api_key::redact_url;
+use crate::syntax::SyntaxError;
+use crate::{ErrorRecord1, ReqError};
+
+/// Error codes documented for the MailboxValidator API.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::ApiErrorCode;
+///
+/// let code = ApiErrorCode::from(10003);
+/// assert_eq!(code, ApiErrorCode::KeyExpired);
+/// assert!(code.is_auth_problem());
+/// assert!(!code.is_retryable());
+/// assert_eq!(i64::from(ApiErrorCode::Other(12345)), 12345);
+/// ```
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum ApiErrorCode {
+    /// 10000: Missing parameter.
+    MissingParameter,
+    /// 10001: API key not found.
+    KeyNotFound,
+    /// 10002: API key disabled.
+    KeyDisabled,
+    /// 10003: API key expired.
+    KeyExpired,
+    /// 10004: Insufficient credits.
+    InsufficientCredits,
+    /// 10005: Unknown error.
+    Unknown,
+    /// 10006: Invalid email syntax.
+    InvalidSyntax,
+    /// Any code not documented above.
+    Other(i64),
+}
+
+impl ApiErrorCode {
+    /// Returns the numeric error code.
+    pub fn code(self) -> i64 {
+        self.into()
+    }
+
+    /// Returns the HTTP status the API documents for this code.
+    pub fn http_status(self) -> Option<StatusCode> {
+        match self {
+            ApiErrorCode::MissingParameter | ApiErrorCode::InvalidSyntax => Some(StatusCode::BAD_REQUEST),
+            ApiErrorCode::KeyNotFound
+            | ApiErrorCode::KeyDisabled
+            | ApiErrorCode::KeyExpired
+            | ApiErrorCode::InsufficientCredits => Some(StatusCode::UNAUTHORIZED),
+            ApiErrorCode::Unknown => Some(StatusCode::INTERNAL_SERVER_ERROR),
+            ApiErrorCode::Other(_) => None,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ApiErrorCode::Unknown)
+    }
+
+    /// Whether the API key is missing, disabled or expired.
+    pub fn is_auth_problem(self) -> bool {
+        matches!(
+            self,
+            ApiErrorCode::KeyNotFound | ApiErrorCode::KeyDisabled | ApiErrorCode::KeyExpired
+        )
+    }
+
+    /// Whether the account has run out of credits.
+    pub fn is_billing_problem(self) -> bool {
+        matches!(self, ApiErrorCode::InsufficientCredits)
+    }
+
+    /// Whether the request itself was rejected, e.g. for an invalid address.
+    pub fn is_input_problem(self) -> bool {
+        matches!(self, ApiErrorCode::MissingParameter | ApiErrorCode::InvalidSyntax)
+    }
+}
+
+impl From<i64> for ApiErrorCode {
+    fn from(code: i64) -> Self {
+        match code {
+            10000 => ApiErrorCode::MissingParameter,
+            10001 => ApiErrorCode::KeyNotFound,
+            10002 => ApiErrorCode::KeyDisabled,
+            10003 => ApiErrorCode::KeyExpired,
+            10004 => ApiErrorCode::InsufficientCredits,
+            10005 => ApiErrorCode::Unknown,
+            10006 => ApiErrorCode::InvalidSyntax,
+            other => ApiErrorCode::Other(other),
+        }
+    }
+}
+
+impl From<ApiErrorCode> for i64 {
+    fn from(code: ApiErrorCode) -> Self {
+        match code {
+            ApiErrorCode::MissingParameter => 10000,
+            ApiErrorCode::KeyNotFound => 10001,
+            ApiErrorCode::KeyDisabled => 10002,
+            ApiErrorCode::KeyExpired => 10003,
+            ApiErrorCode::InsufficientCredits => 10004,
+            ApiErrorCode::Unknown => 10005,
+            ApiErrorCode::InvalidSyntax => 10006,
+            ApiErrorCode::Other(other) => other,
+        }
+    }
+}
+
+impl fmt::Display for ApiErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Errors returned when calling the MailboxValidator API.
+///
+/// Neither `Display` nor `Debug` output contains the API key.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use mailboxvalidator::{MailboxValidatorClient, MailboxValidatorError};
+///
+/// let client = MailboxValidatorClient::builder("SECRET-KEY")
+///     .base_url("http://127.0.0.1:1/v2/")
+///     .build()?;
+///
+/// let err = client.validate_email("example@example.com").unwrap_err();
+/// assert!(matches!(err, MailboxValidatorError::Transport(_)));
+/// assert!(!format!("{}", err).contains("SECRET-KEY"));
+/// assert!(!format!("{:?}", err).contains("SECRET-KEY"));
+/// # Ok::<(), MailboxValidatorError>(())
+/// ```
+#[derive(Debug)]
+pub enum MailboxValidatorError {
+    /// The configured base URL is not a valid URL.
+    InvalidBaseUrl(url::ParseError),
+    /// The request could not be sent or the response could not be read.
+    ///
+    /// The API key in the error's URL is masked.
+    Transport(ReqError),
+    /// The API answered with a body that does not match the expected record.
+    Decode(serde_json::Error),
+    /// The API answered with an error object.
+    Api(ErrorRecord1),
+    /// The API answered with a status code and a body that carries no error object.
+    UnexpectedStatus {
+        /// HTTP status of the response.
+        status: StatusCode,
+        /// Raw response body.
+        body: String,
+    },
+    /// The address failed the client's offline syntax check and was not sent.
+    InvalidSyntax(SyntaxError),
+    /// The client's [`Budget`](crate::Budget) is spent, so the request was not sent.
+    BudgetExhausted,
+}
+
+impl MailboxValidatorError {
+    /// Returns the API error code, if the API returned an error object.
+    ///
+    /// An address rejected by the offline syntax check reports
+    /// [`ApiErrorCode::InvalidSyntax`], as the API would have.
+    pub fn api_error_code(&self) -> Option<ApiErrorCode> {
+        match self {
+            MailboxValidatorError::Api(err) => Some(err.error_code),
+            MailboxValidatorError::InvalidSyntax(_) => Some(ApiErrorCode::InvalidSyntax),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MailboxValidatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MailboxValidatorError::InvalidBaseUrl(err) => write!(f, "invalid MailboxValidator API base URL: {}", err),
+            MailboxValidatorError::Transport(err) => write!(f, "request to MailboxValidator API failed: {}", err),
+            MailboxValidatorError::Decode(err) => write!(f, "unable to decode MailboxValidator API response: {}", err),
+            MailboxValidatorError::Api(err) => write!(f, "MailboxValidator API error {}: {}", err.error_code, err.error_message),
+            MailboxValidatorError::UnexpectedStatus { status, .. } => write!(f, "unexpected HTTP status from MailboxValidator API: {}", status),
+            MailboxValidatorError::InvalidSyntax(err) => write!(f, "{}", err),
+            MailboxValidatorError::BudgetExhausted => write!(f, "credit budget exhausted, request not sent"),
+        }
+    }
+}
+
+impl Error for MailboxValidatorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MailboxValidatorError::InvalidBaseUrl(err) => Some(err),
+            MailboxValidatorError::Transport(err) => Some(err),
+            MailboxValidatorError::Decode(err) => Some(err),
+            MailboxValidatorError::InvalidSyntax(err) => Some(err),
+            MailboxValidatorError::Api(_)
+            | MailboxValidatorError::UnexpectedStatus { .. }
+            | MailboxValidatorError::BudgetExhausted => None,
+        }
+    }
+}
+
+impl From<ReqError> for MailboxValidatorError {
+    /// Wraps the error, masking the API key in its URL.
+    fn from(mut err: ReqError) -> Self {
+        if let Some(url) = err.url_mut() {
+            redact_url(url);
+        }
+        MailboxValidatorError::Transport(err)
+    }
+}
+
+impl From<serde_json::Error> for MailboxValidatorError {
+    fn from(err: serde_json::Error) -> Self {
+        MailboxValidatorError::Decode(err)
+    }
+}
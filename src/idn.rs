@@ -0,0 +1,29 @@
+//! Conversion of internationalized domain names (RFC 5891, UTS #46).
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::borrow::Cow;
+
+/// The ASCII (punycode) form of `domain`, lowercased, or `None` if it is
+/// not a valid internationalized domain name.
+pub(crate) fn domain_to_ascii(domain: &str) -> Option<String> {
+    idna::domain_to_ascii(domain).ok().filter(|ascii| !ascii.is_empty())
+}
+
+/// The Unicode form of `domain`, lowercased. Labels that cannot be decoded
+/// are kept as they are.
+pub(crate) fn domain_to_unicode(domain: &str) -> String {
+    idna::domain_to_unicode(domain).0
+}
+
+/// `email_address` with its domain in ASCII form, for sending to the API.
+/// Addresses whose domain cannot be converted are returned unchanged.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn email_to_ascii(email_address: &str) -> Cow<'_, str> {
+    match email_address.rsplit_once('@') {
+        Some((local_part, domain)) if !domain.is_ascii() => match domain_to_ascii(domain) {
+            Some(domain) => Cow::Owned(format!("{}@{}", local_part, domain)),
+            None => Cow::Borrowed(email_address),
+        },
+        _ => Cow::Borrowed(email_address),
+    }
+}
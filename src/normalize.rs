@@ -0,0 +1,115 @@
+//! Offline normalization of addresses to the mailbox they deliver to.
+
+use crate::idn;
+
+/// How a provider lets users vary the local part of their address.
+#[derive(Clone, Copy)]
+struct Rules {
+    /// The domain every alias domain of the provider is folded into.
+    domain: &'static str,
+    /// Character starting a tag that the provider ignores, e.g. `+`.
+    tag: Option<char>,
+    /// Whether dots in the local part are ignored.
+    ignore_dots: bool,
+}
+
+const fn rules(domain: &'static str, tag: Option<char>, ignore_dots: bool) -> Rules {
+    Rules { domain, tag, ignore_dots }
+}
+
+const GMAIL: Rules = rules("gmail.com", Some('+'), true);
+const PLUS: Rules = rules("", Some('+'), false);
+const YAHOO: Rules = rules("", Some('-'), false);
+
+/// Providers with known addressing rules. An empty `domain` keeps the
+/// address's own domain.
+const PROVIDERS: &[(&str, Rules)] = &[
+    ("gmail.com", GMAIL),
+    ("googlemail.com", GMAIL),
+    ("outlook.com", PLUS),
+    ("hotmail.com", PLUS),
+    ("hotmail.co.uk", PLUS),
+    ("hotmail.fr", PLUS),
+    ("live.com", PLUS),
+    ("msn.com", PLUS),
+    ("yahoo.com", YAHOO),
+    ("yahoo.co.uk", YAHOO),
+    ("yahoo.fr", YAHOO),
+    ("ymail.com", YAHOO),
+    ("rocketmail.com", YAHOO),
+    ("fastmail.com", PLUS),
+    ("fastmail.fm", PLUS),
+    ("icloud.com", PLUS),
+    ("me.com", PLUS),
+    ("mac.com", PLUS),
+    ("protonmail.com", PLUS),
+    ("protonmail.ch", PLUS),
+    ("proton.me", PLUS),
+    ("pm.me", PLUS),
+    ("yandex.com", PLUS),
+    ("yandex.ru", PLUS),
+    ("zoho.com", PLUS),
+];
+
+/// Reduces `email_address` to the base address of the mailbox it delivers
+/// to, as the API reports in `base_email_address`.
+///
+/// The whole address is trimmed and lowercased for every domain, including
+/// its local part, so `Jane@Example.com` and `jane@example.com` share a
+/// cache entry even though a server may in principle treat them as
+/// different mailboxes. An internationalized domain is converted to its
+/// ASCII (punycode) form. For known providers, tags such as `+news` (or
+/// `-news` at Yahoo) are removed, dots are removed at Gmail,
+/// `googlemail.com` becomes `gmail.com`, and Fastmail's subdomain
+/// addressing `anything@user.fastmail.com` becomes `user@fastmail.com`.
+/// Beyond lowercasing, the local parts of other domains, quoted local parts
+/// and input without an `@` are kept, since their tags may name different
+/// mailboxes.
+///
+/// Normalizing needs no request, so it suits deduplicating a list before
+/// paying for validations. Result caches key entries on the normalized
+/// address.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::normalize;
+///
+/// assert_eq!(normalize(" J.Doe+News@GoogleMail.com "), "jdoe@gmail.com");
+/// assert_eq!(normalize("jane.doe+shop@outlook.com"), "jane.doe@outlook.com");
+/// assert_eq!(normalize("jane-shop@yahoo.com"), "jane@yahoo.com");
+/// assert_eq!(normalize("news@jane.fastmail.com"), "jane@fastmail.com");
+/// assert_eq!(normalize("Jane+Shop@Example.com"), "jane+shop@example.com");
+/// assert_eq!(normalize("\"Jane Doe\"@example.com"), "\"jane doe\"@example.com");
+/// assert_eq!(normalize("Jürgen@Bücher.example"), "jürgen@xn--bcher-kva.example");
+/// ```
+pub fn normalize(email_address: &str) -> String {
+    let email_address = email_address.trim().to_lowercase();
+    let Some((local_part, domain)) = email_address.rsplit_once('@') else {
+        return email_address;
+    };
+    let domain = domain.trim_end_matches('.');
+    let domain = idn::domain_to_ascii(domain).unwrap_or_else(|| domain.to_string());
+    if local_part.starts_with('"') {
+        return format!("{}@{}", local_part, domain);
+    }
+
+    // Fastmail delivers anything@user.fastmail.com to user@fastmail.com.
+    let (local_part, domain) = match domain.strip_suffix(".fastmail.com") {
+        Some(user) if !user.contains('.') => (user, "fastmail.com"),
+        _ => (local_part, domain.as_str()),
+    };
+
+    let Some(&(_, rules)) = PROVIDERS.iter().find(|(provider, _)| *provider == domain) else {
+        return format!("{}@{}", local_part, domain);
+    };
+    let mut base = match rules.tag.and_then(|tag| local_part.split_once(tag)) {
+        Some((base, _)) if !base.is_empty() => base.to_string(),
+        _ => local_part.to_string(),
+    };
+    if rules.ignore_dots {
+        base.retain(|c| c != '.');
+    }
+    let domain = if rules.domain.is_empty() { domain } else { rules.domain };
+    format!("{}@{}", base, domain)
+}
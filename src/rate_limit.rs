@@ -0,0 +1,167 @@
+//! Client-side rate limiting.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::time::Duration;
+
+/// Token-bucket rate limiter shared by every clone of a client.
+///
+/// Up to `burst` requests may be sent at once; after that requests are
+/// spaced out to `requests_per_second`. Callers that exceed the rate wait
+/// for capacity rather than fail. Clones share the same bucket, so one
+/// limiter can also be passed to several clients.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use mailboxvalidator::{MailboxValidatorClient, RateLimiter};
+///
+/// let limiter = RateLimiter::new(5.0, 10);
+///
+/// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .rate_limiter(limiter.clone())
+///     .build()?;
+/// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    #[cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code))]
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code))]
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `requests_per_second` on average and up to
+    /// `burst` requests at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not a positive number.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second > 0.0 && requests_per_second.is_finite(),
+            "requests_per_second must be positive"
+        );
+        let burst = f64::from(burst.max(1));
+        RateLimiter {
+            bucket: Arc::new(Mutex::new(Bucket {
+                rate: requests_per_second,
+                burst,
+                tokens: burst,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Takes one token and returns how long the caller must wait before
+    /// sending its request.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    /// Like [`RateLimiter::reserve`], at the time `now`.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.rate).min(bucket.burst);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / bucket.rate)
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::RateLimiter;
+
+    /// The time the limiter was created, which its first reservations
+    /// are measured from.
+    fn created(limiter: &RateLimiter) -> Instant {
+        limiter.bucket.lock().unwrap().updated
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    fn assert_close(actual: Duration, expected: Duration) {
+        let difference = actual.abs_diff(expected);
+        assert!(difference < Duration::from_micros(1), "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn burst_is_free_then_spaced_by_the_rate() {
+        let limiter = RateLimiter::new(4.0, 3);
+        let start = created(&limiter);
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        }
+        for n in 1..=4 {
+            assert_close(limiter.reserve_at(start), secs(0.25 * f64::from(n)));
+        }
+    }
+
+    #[test]
+    fn tokens_refill_over_time() {
+        let limiter = RateLimiter::new(2.0, 2);
+        let start = created(&limiter);
+        limiter.reserve_at(start);
+        limiter.reserve_at(start);
+        assert_close(limiter.reserve_at(start), secs(0.5));
+
+        // The third request was due at 0.5s; by 1.0s one more token has
+        // refilled.
+        let later = start + secs(1.0);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_close(limiter.reserve_at(later), secs(0.5));
+    }
+
+    #[test]
+    fn tokens_are_capped_at_the_burst() {
+        let limiter = RateLimiter::new(10.0, 2);
+        let later = created(&limiter) + secs(60.0);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_close(limiter.reserve_at(later), secs(0.1));
+    }
+
+    #[test]
+    fn burst_is_at_least_one() {
+        let limiter = RateLimiter::new(1.0, 0);
+        let start = created(&limiter);
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_close(limiter.reserve_at(start), secs(1.0));
+    }
+
+    #[test]
+    fn clones_share_the_bucket() {
+        let limiter = RateLimiter::new(1.0, 1);
+        let start = created(&limiter);
+        assert_eq!(limiter.clone().reserve_at(start), Duration::ZERO);
+        assert_close(limiter.reserve_at(start), secs(1.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "requests_per_second must be positive")]
+    fn rate_must_be_positive() {
+        RateLimiter::new(0.0, 1);
+    }
+}
@@ -0,0 +1,311 @@
+//! Typed records returned by the MailboxValidator API.
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::idn;
+use crate::ApiErrorCode;
+
+/// MailboxValidator Single Validation API result record.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct SingleEmailValidationRecord {
+    /// The input email address.
+    pub email_address: String,
+    /// The input email address after sanitizing the username of the dots (only Gmail) and subaddressing.
+    pub base_email_address: String,
+    /// The domain of the email address.
+    pub domain: String,
+    /// Whether the email address is from a free email provider like Gmail or Hotmail.
+    pub is_free: Option<bool>,
+    /// Whether the email address is syntactically correct.
+    pub is_syntax: Option<bool>,
+    /// Whether the email address has a valid MX record in its DNS entries.
+    pub is_domain: Option<bool>,
+    /// Whether the mail servers specified in the MX records are responding to connections.
+    pub is_smtp: Option<bool>,
+    /// Whether the mail server confirms that the email address actually exist.
+    pub is_verified: Option<bool>,
+    /// Whether the mail server is currently down or unresponsive.
+    pub is_server_down: Option<bool>,
+    /// Whether the mail server employs greylisting.
+    pub is_greylisted: Option<bool>,
+    /// Whether the email address is a temporary one from a disposable email provider.
+    pub is_disposable: Option<bool>,
+    /// Whether the email address is in the MailboxValidator blacklist.
+    pub is_suppressed: Option<bool>,
+    /// Whether the email address is a role-based email address like admin@example.net.
+    pub is_role: Option<bool>,
+    /// Whether the email address contains high risk keywords.
+    pub is_high_risk: Option<bool>,
+    /// Whether the email address is a catch-all address.
+    pub is_catchall: Option<bool>,
+    /// Whether the email domain is enforcing DMARC.
+    pub is_dmarc_enforced: Option<bool>,
+    /// Whether the email domain is using strict SPF.
+    pub is_strict_spf: Option<bool>,
+    /// Whether the email domain is a reachable website.
+    pub website_exist: Option<bool>,
+    /// Whether the email address is valid based on all the previous fields.
+    pub status: Option<bool>,
+    /// Email address reputation score. Score > 0.70 means good; score > 0.40 means fair; score <= 0.40 means poor.
+    pub mailboxvalidator_score: f64,
+    /// The time taken to get the results in seconds.
+    pub time_taken: f64,
+    /// The number of credits left to perform validations.
+    pub credits_available: i64,
+    /// Where this result came from. Not part of the API response.
+    #[serde(skip)]
+    pub origin: ResultOrigin,
+    /// The address with its domain corrected, if the client suggests typo
+    /// corrections and the address is not valid. Not part of the API response.
+    #[serde(skip)]
+    pub suggestion: Option<String>,
+}
+
+/// MailboxValidator Disposable Email API result record.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct DisposableEmailRecord {
+    /// The input email address.
+    pub email_address: String,
+    /// Whether the email address is a temporary one from a disposable email provider.
+    pub is_disposable: Option<bool>,
+    /// The number of credits left to perform validations.
+    pub credits_available: i64,
+    /// Where this result came from. Not part of the API response.
+    #[serde(skip)]
+    pub origin: ResultOrigin,
+}
+
+/// MailboxValidator Free Email API result record.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct FreeEmailRecord {
+    /// The input email address.
+    pub email_address: String,
+    /// Whether the email address is from a free email provider like Gmail or Hotmail.
+    pub is_free: Option<bool>,
+    /// The number of credits left to perform validations.
+    pub credits_available: i64,
+    /// Where this result came from. Not part of the API response.
+    #[serde(skip)]
+    pub origin: ResultOrigin,
+}
+
+/// Where a result record came from.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum ResultOrigin {
+    /// Returned by the MailboxValidator API, spending a credit.
+    #[default]
+    Api,
+    /// Served from the client's [`Cache`](crate::Cache) without a request.
+    Cache,
+    /// Decided by the client without a request, e.g. by the offline syntax check.
+    Local,
+}
+
+impl SingleEmailValidationRecord {
+    /// The domain in ASCII form, with internationalized labels in punycode,
+    /// whichever form the API returned.
+    pub fn domain_ascii(&self) -> String {
+        domain_ascii(self.domain())
+    }
+
+    /// The domain in Unicode form, with punycode labels decoded, whichever
+    /// form the API returned.
+    pub fn domain_unicode(&self) -> String {
+        idn::domain_to_unicode(self.domain())
+    }
+
+    fn domain(&self) -> &str {
+        if self.domain.is_empty() {
+            email_domain(&self.email_address)
+        } else {
+            &self.domain
+        }
+    }
+}
+
+impl DisposableEmailRecord {
+    /// The domain of the email address in ASCII form, with
+    /// internationalized labels in punycode.
+    pub fn domain_ascii(&self) -> String {
+        domain_ascii(email_domain(&self.email_address))
+    }
+
+    /// The domain of the email address in Unicode form, with punycode
+    /// labels decoded.
+    pub fn domain_unicode(&self) -> String {
+        idn::domain_to_unicode(email_domain(&self.email_address))
+    }
+}
+
+impl FreeEmailRecord {
+    /// The domain of the email address in ASCII form, with
+    /// internationalized labels in punycode.
+    pub fn domain_ascii(&self) -> String {
+        domain_ascii(email_domain(&self.email_address))
+    }
+
+    /// The domain of the email address in Unicode form, with punycode
+    /// labels decoded.
+    pub fn domain_unicode(&self) -> String {
+        idn::domain_to_unicode(email_domain(&self.email_address))
+    }
+}
+
+/// The part of `email_address` after its last `@`, if any.
+fn email_domain(email_address: &str) -> &str {
+    email_address.rsplit_once('@').map_or("", |(_, domain)| domain)
+}
+
+/// The ASCII form of `domain`, or `domain` lowercased if it cannot be
+/// converted.
+fn domain_ascii(domain: &str) -> String {
+    idn::domain_to_ascii(domain).unwrap_or_else(|| domain.to_lowercase())
+}
+
+/// A result record that can be cached.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) trait Record: Serialize + DeserializeOwned {
+    fn set_origin(&mut self, origin: ResultOrigin);
+
+    /// Relabels the record with `email_address`, filling in address fields
+    /// that a cache left blank.
+    fn set_email_address(&mut self, email_address: &str);
+
+    fn credits_available(&self) -> i64;
+
+    /// Whether the result may be cached. Results the API could not decide
+    /// are worth asking for again.
+    fn is_cacheable(&self) -> bool;
+
+    /// The local result for an address that failed the offline syntax
+    /// check, if this kind of record can express it.
+    fn invalid_syntax(_email_address: &str) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl Record for SingleEmailValidationRecord {
+    fn set_origin(&mut self, origin: ResultOrigin) {
+        self.origin = origin;
+    }
+
+    fn set_email_address(&mut self, email_address: &str) {
+        self.email_address = email_address.to_string();
+        if self.base_email_address.is_empty() {
+            self.base_email_address = crate::normalize(email_address);
+        }
+        if self.domain.is_empty() {
+            self.domain = email_domain(email_address).to_string();
+        }
+    }
+
+    fn credits_available(&self) -> i64 {
+        self.credits_available
+    }
+
+    fn is_cacheable(&self) -> bool {
+        self.status.is_some()
+    }
+
+    fn invalid_syntax(email_address: &str) -> Option<Self> {
+        Some(SingleEmailValidationRecord {
+            email_address: email_address.to_string(),
+            base_email_address: String::new(),
+            domain: email_address.rsplit_once('@').map(|(_, domain)| domain.to_string()).unwrap_or_default(),
+            is_free: None,
+            is_syntax: Some(false),
+            is_domain: None,
+            is_smtp: None,
+            is_verified: None,
+            is_server_down: None,
+            is_greylisted: None,
+            is_disposable: None,
+            is_suppressed: None,
+            is_role: None,
+            is_high_risk: None,
+            is_catchall: None,
+            is_dmarc_enforced: None,
+            is_strict_spf: None,
+            website_exist: None,
+            status: Some(false),
+            mailboxvalidator_score: 0.0,
+            time_taken: 0.0,
+            credits_available: 0,
+            origin: ResultOrigin::Local,
+            suggestion: None,
+        })
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl Record for DisposableEmailRecord {
+    fn set_origin(&mut self, origin: ResultOrigin) {
+        self.origin = origin;
+    }
+
+    fn set_email_address(&mut self, email_address: &str) {
+        self.email_address = email_address.to_string();
+    }
+
+    fn credits_available(&self) -> i64 {
+        self.credits_available
+    }
+
+    fn is_cacheable(&self) -> bool {
+        self.is_disposable.is_some()
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl Record for FreeEmailRecord {
+    fn set_origin(&mut self, origin: ResultOrigin) {
+        self.origin = origin;
+    }
+
+    fn set_email_address(&mut self, email_address: &str) {
+        self.email_address = email_address.to_string();
+    }
+
+    fn credits_available(&self) -> i64 {
+        self.credits_available
+    }
+
+    fn is_cacheable(&self) -> bool {
+        self.is_free.is_some()
+    }
+}
+
+/// MailboxValidator Error object
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorRecord {
+    /// The error details.
+    pub error: ErrorRecord1,
+}
+
+/// MailboxValidator Error Response object
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorRecord1 {
+    /// The error code, see the Error Codes reference.
+    #[serde(with = "error_code")]
+    pub error_code: ApiErrorCode,
+    /// The error message.
+    pub error_message: String,
+}
+
+mod error_code {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::ApiErrorCode;
+
+    pub fn serialize<S: Serializer>(code: &ApiErrorCode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(code.code())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ApiErrorCode, D::Error> {
+        i64::deserialize(deserializer).map(ApiErrorCode::from)
+    }
+}
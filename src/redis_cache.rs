@@ -0,0 +1,188 @@
+//! Result cache shared through Redis.
+
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use redis::{Client, Commands, Connection};
+use sha2::{Digest, Sha256};
+
+use crate::Cache;
+
+/// Fields of a cached result holding the address or its domain.
+const ADDRESS_FIELDS: &[&str] = &["email_address", "base_email_address", "domain"];
+
+/// How long to wait for Redis before treating a lookup as a miss.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// [`Cache`] stored in Redis, so every replica of a service shares results.
+///
+/// Entries are stored under `<namespace>:<key>` with `SETEX`, so Redis
+/// expires them itself. With [`RedisCache::hash_keys`] the key part is a
+/// SHA-256 digest and the address fields of each result are blanked, so raw
+/// addresses are never written to Redis. A failed connection counts as a
+/// miss and is retried on the next lookup. Clones share the connection.
+///
+/// # Examples
+///
+/// Runs against the server in `MBV_REDIS_URL`, e.g.
+/// `MBV_REDIS_URL=redis://127.0.0.1/ cargo test --features redis-cache`,
+/// and is skipped when it is not set.
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use std::time::Duration;
+///
+/// use mailboxvalidator::{Cache, MailboxValidatorClient, RedisCache};
+///
+/// let Ok(url) = std::env::var("MBV_REDIS_URL") else {
+///     return Ok(());
+/// };
+/// let cache = RedisCache::open(url.as_str())?.namespace("mbv-doctest").hash_keys(true);
+/// let value = r#"{"email_address":"a@example.com","domain":"example.com","status":true}"#;
+/// cache.put("validation/single:a@example.com", value.to_string(), Duration::from_secs(60));
+/// assert_eq!(
+///     cache.get("validation/single:a@example.com").as_deref(),
+///     Some(r#"{"domain":"","email_address":"","status":true}"#)
+/// );
+/// assert_eq!(cache.get("validation/single:b@example.com"), None);
+///
+/// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .cache(cache)
+///     .build()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct RedisCache {
+    client: Client,
+    conn: Arc<Mutex<Option<Connection>>>,
+    namespace: String,
+    hash_keys: bool,
+}
+
+impl RedisCache {
+    /// Connects to the Redis server at `url`, e.g. `redis://127.0.0.1/`.
+    ///
+    /// Keys are stored under the `mailboxvalidator` namespace, unhashed.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the URL is invalid or the server cannot be reached.
+    pub fn open(url: &str) -> redis::RedisResult<Self> {
+        let client = Client::open(url)?;
+        let conn = connect(&client)?;
+        Ok(RedisCache {
+            client,
+            conn: Arc::new(Mutex::new(Some(conn))),
+            namespace: "mailboxvalidator".to_string(),
+            hash_keys: false,
+        })
+    }
+
+    /// Sets the prefix of every key, e.g. to keep environments apart.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Stores a SHA-256 digest of each key instead of the address itself,
+    /// and blanks the `email_address`, `base_email_address` and `domain` of
+    /// each stored result.
+    ///
+    /// Results read back are relabelled with the address that was looked up,
+    /// so clients see the same fields either way.
+    pub fn hash_keys(mut self, hash_keys: bool) -> Self {
+        self.hash_keys = hash_keys;
+        self
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        if !self.hash_keys {
+            return format!("{}:{}", self.namespace, key);
+        }
+        let mut redis_key = format!("{}:", self.namespace);
+        for byte in Sha256::digest(key.as_bytes()) {
+            let _ = write!(redis_key, "{:02x}", byte);
+        }
+        redis_key
+    }
+
+    /// Runs `command`, reconnecting first if the last command failed.
+    fn with_conn<T>(&self, command: impl FnOnce(&mut Connection) -> redis::RedisResult<T>) -> Option<T> {
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        if conn.is_none() {
+            *conn = connect(&self.client).ok();
+        }
+        let result = command(conn.as_mut()?);
+        if result.is_err() {
+            *conn = None;
+        }
+        result.ok()
+    }
+}
+
+impl fmt::Debug for RedisCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisCache")
+            .field("namespace", &self.namespace)
+            .field("hash_keys", &self.hash_keys)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Cache for RedisCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let key = self.redis_key(key);
+        self.with_conn(|conn| conn.get(&key)).flatten()
+    }
+
+    fn put(&self, key: &str, value: String, ttl: Duration) {
+        let key = self.redis_key(key);
+        let value = if self.hash_keys { redact(value) } else { value };
+        let seconds = ttl.as_secs().max(1);
+        self.with_conn(|conn| conn.set_ex::<_, _, ()>(&key, value, seconds));
+    }
+}
+
+/// Blanks the fields of a serialized result that hold the address or its
+/// domain. Values that are not JSON objects are stored as they are.
+fn redact(value: String) -> String {
+    let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_str(&value) else {
+        return value;
+    };
+    for name in ADDRESS_FIELDS {
+        if let Some(field) = fields.get_mut(*name) {
+            *field = serde_json::Value::String(String::new());
+        }
+    }
+    serde_json::Value::Object(fields).to_string()
+}
+
+fn connect(client: &Client) -> redis::RedisResult<Connection> {
+    let conn = client.get_connection_with_timeout(TIMEOUT)?;
+    conn.set_read_timeout(Some(TIMEOUT))?;
+    conn.set_write_timeout(Some(TIMEOUT))?;
+    Ok(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::redact;
+
+    #[test]
+    fn redact_blanks_address_fields() {
+        let value = r#"{"email_address":"J.Doe+news@gmail.com","base_email_address":"jdoe@gmail.com","domain":"gmail.com","status":true,"credits_available":5}"#;
+        let redacted = redact(value.to_string());
+        assert!(!redacted.contains("doe") && !redacted.contains("gmail"), "{}", redacted);
+        let fields: serde_json::Value = serde_json::from_str(&redacted).unwrap();
+        assert_eq!(fields["email_address"], "");
+        assert_eq!(fields["status"], true);
+        assert_eq!(fields["credits_available"], 5);
+    }
+
+    #[test]
+    fn redact_keeps_other_values() {
+        assert_eq!(redact("not json".to_string()), "not json");
+        assert_eq!(redact("[1,2]".to_string()), "[1,2]");
+    }
+}
@@ -0,0 +1,365 @@
+//! Retry policy for transient failures.
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::collections::hash_map::RandomState;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::time::SystemTime;
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use reqwest::StatusCode;
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::MailboxValidatorError;
+
+/// Failures that a [`RetryPolicy`] may retry.
+///
+/// API errors other than 10005 (unknown error) are never retried, whatever
+/// these flags say, since sending the same request again cannot succeed.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct RetryOn {
+    /// Connection failures and connections reset before a response arrived.
+    pub network: bool,
+    /// Requests that exceeded the client timeout.
+    pub timeout: bool,
+    /// HTTP 5xx responses, including API error 10005.
+    pub server_error: bool,
+    /// HTTP 429 responses.
+    pub too_many_requests: bool,
+}
+
+impl Default for RetryOn {
+    fn default() -> Self {
+        RetryOn {
+            network: true,
+            timeout: true,
+            server_error: true,
+            too_many_requests: true,
+        }
+    }
+}
+
+/// How a client retries requests that failed for transient reasons.
+///
+/// Delays grow exponentially from `base_delay`, are capped at `max_delay`
+/// and, with jitter enabled, are drawn at random from the upper half of
+/// that range. A `Retry-After` header on the failed response takes
+/// precedence when it asks for a longer wait; if it asks for more than
+/// `max_delay` the request is not retried.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use std::time::Duration;
+/// use mailboxvalidator::{MailboxValidatorClient, RetryOn, RetryPolicy};
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_millis(200))
+///     .retry_on(RetryOn { timeout: false, ..RetryOn::default() });
+///
+/// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .retry_policy(policy)
+///     .build()?;
+/// # Ok::<(), mailboxvalidator::MailboxValidatorError>(())
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    respect_retry_after: bool,
+    retry_on: RetryOn,
+}
+
+impl RetryPolicy {
+    /// Up to 3 attempts, starting at 500 ms and capped at 30 s, with jitter.
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            respect_retry_after: true,
+            retry_on: RetryOn::default(),
+        }
+    }
+
+    /// Never retries. This is what clients use unless configured otherwise.
+    pub fn none() -> Self {
+        RetryPolicy::new().max_attempts(1)
+    }
+
+    /// Sets the total number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the longest delay between two attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Enables or disables random jitter on the delays.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Enables or disables honoring the `Retry-After` response header.
+    pub fn respect_retry_after(mut self, respect_retry_after: bool) -> Self {
+        self.respect_retry_after = respect_retry_after;
+        self
+    }
+
+    /// Sets which failures are retried.
+    pub fn retry_on(mut self, retry_on: RetryOn) -> Self {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// Returns how long to wait before retrying after `attempt` failed with
+    /// `err`, or `None` if the error should be returned.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn next_delay(
+        &self,
+        attempt: u32,
+        err: &MailboxValidatorError,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.should_retry(err) {
+            return None;
+        }
+
+        let exponent = (attempt - 1).min(31);
+        let mut delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        if self.jitter {
+            let half = delay / 2;
+            delay = half + half.mul_f64(random_fraction());
+        }
+
+        match retry_after {
+            Some(wait) if self.respect_retry_after => {
+                if wait > self.max_delay {
+                    None
+                } else {
+                    Some(wait.max(delay))
+                }
+            }
+            _ => Some(delay),
+        }
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn should_retry(&self, err: &MailboxValidatorError) -> bool {
+        match err {
+            MailboxValidatorError::Transport(err) => {
+                if err.is_timeout() {
+                    self.retry_on.timeout
+                } else {
+                    (err.is_connect() || err.is_request() || err.is_body()) && self.retry_on.network
+                }
+            }
+            MailboxValidatorError::Api(err) => err.error_code.is_retryable() && self.retry_on.server_error,
+            MailboxValidatorError::UnexpectedStatus { status, .. } => {
+                if *status == StatusCode::TOO_MANY_REQUESTS {
+                    self.retry_on.too_many_requests
+                } else {
+                    status.is_server_error() && self.retry_on.server_error
+                }
+            }
+            MailboxValidatorError::InvalidBaseUrl(_)
+            | MailboxValidatorError::Decode(_)
+            | MailboxValidatorError::InvalidSyntax(_)
+            | MailboxValidatorError::BudgetExhausted => false,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new()
+    }
+}
+
+/// Reads a `Retry-After` header given either in seconds or as an HTTP date.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::StatusCode;
+
+    use super::{retry_after, RetryOn, RetryPolicy};
+    use crate::{ApiErrorCode, ErrorRecord1, MailboxValidatorError};
+
+    fn status(status: StatusCode) -> MailboxValidatorError {
+        MailboxValidatorError::UnexpectedStatus {
+            status,
+            body: String::new(),
+        }
+    }
+
+    fn api(code: i64) -> MailboxValidatorError {
+        MailboxValidatorError::Api(ErrorRecord1 {
+            error_code: ApiErrorCode::from(code),
+            error_message: String::new(),
+        })
+    }
+
+    fn steady() -> RetryPolicy {
+        RetryPolicy::new()
+            .max_attempts(10)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .jitter(false)
+    }
+
+    #[test]
+    fn delays_double_up_to_max_delay() {
+        let policy = steady();
+        let err = status(StatusCode::SERVICE_UNAVAILABLE);
+        let delays: Vec<_> = (1..=6).map(|attempt| policy.next_delay(attempt, &err, None)).collect();
+        let millis = |ms| Some(Duration::from_millis(ms));
+        assert_eq!(delays, [millis(100), millis(200), millis(400), millis(800), millis(1000), millis(1000)]);
+    }
+
+    #[test]
+    fn large_attempt_numbers_do_not_overflow() {
+        let policy = steady().max_attempts(u32::MAX);
+        let err = status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(policy.next_delay(100, &err, None), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn jitter_stays_in_upper_half() {
+        let policy = steady().jitter(true);
+        let err = status(StatusCode::SERVICE_UNAVAILABLE);
+        for _ in 0..100 {
+            let delay = policy.next_delay(3, &err, None).unwrap();
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn stops_at_max_attempts() {
+        let policy = steady().max_attempts(3);
+        let err = status(StatusCode::BAD_GATEWAY);
+        assert!(policy.next_delay(1, &err, None).is_some());
+        assert!(policy.next_delay(2, &err, None).is_some());
+        assert_eq!(policy.next_delay(3, &err, None), None);
+        assert_eq!(RetryPolicy::none().next_delay(1, &err, None), None);
+    }
+
+    #[test]
+    fn only_unknown_api_errors_are_retried() {
+        let policy = steady();
+        assert!(policy.next_delay(1, &api(10005), None).is_some());
+        for code in [10000, 10001, 10002, 10003, 10004, 10006, 12345] {
+            assert_eq!(policy.next_delay(1, &api(code), None), None, "error {}", code);
+        }
+    }
+
+    #[test]
+    fn local_failures_are_not_retried() {
+        let policy = steady();
+        assert_eq!(policy.next_delay(1, &MailboxValidatorError::BudgetExhausted, None), None);
+        assert_eq!(policy.next_delay(1, &status(StatusCode::BAD_REQUEST), None), None);
+    }
+
+    #[test]
+    fn retry_on_flags_are_honoured() {
+        let policy = steady().retry_on(RetryOn {
+            server_error: false,
+            ..RetryOn::default()
+        });
+        assert_eq!(policy.next_delay(1, &status(StatusCode::SERVICE_UNAVAILABLE), None), None);
+        assert_eq!(policy.next_delay(1, &api(10005), None), None);
+        assert!(policy.next_delay(1, &status(StatusCode::TOO_MANY_REQUESTS), None).is_some());
+
+        let policy = steady().retry_on(RetryOn {
+            too_many_requests: false,
+            ..RetryOn::default()
+        });
+        assert_eq!(policy.next_delay(1, &status(StatusCode::TOO_MANY_REQUESTS), None), None);
+    }
+
+    #[test]
+    fn retry_after_extends_but_never_shortens_the_delay() {
+        let policy = steady();
+        let err = status(StatusCode::TOO_MANY_REQUESTS);
+        let wait = Some(Duration::from_millis(700));
+        assert_eq!(policy.next_delay(1, &err, wait), wait);
+        assert_eq!(policy.next_delay(4, &err, wait), Some(Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn retry_after_beyond_max_delay_gives_up() {
+        let policy = steady();
+        let err = status(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(policy.next_delay(1, &err, Some(Duration::from_secs(5))), None);
+
+        let policy = steady().respect_retry_after(false);
+        assert_eq!(policy.next_delay(1, &err, Some(Duration::from_secs(5))), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn retry_after_header_in_seconds_or_as_date() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(" 120 "));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&later).unwrap());
+        let wait = retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(55) && wait <= Duration::from_secs(60), "{:?}", wait);
+
+        let past = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(60));
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&past).unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
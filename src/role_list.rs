@@ -0,0 +1,155 @@
+//! Offline detection of role addresses such as `admin@` or `support@`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Role local parts shipped with the crate, by language.
+const BUNDLED: &[(&str, &str)] = &[
+    ("en", include_str!("../data/roles/en.txt")),
+    ("de", include_str!("../data/roles/de.txt")),
+    ("es", include_str!("../data/roles/es.txt")),
+    ("fr", include_str!("../data/roles/fr.txt")),
+];
+
+/// Set of local parts that belong to a role rather than a person.
+///
+/// An address is a role address if its whole local part, ignoring case and
+/// any `+tag`, is listed. Multi-word roles are listed with each spelling,
+/// such as `no-reply` and `do_not_reply`. Local parts that merely start with
+/// a role, such as `dev.patel@` or `hr_jones@`, usually belong to a person
+/// and only match with [`RoleList::match_prefixes`].
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::RoleList;
+///
+/// let mut roles = RoleList::localized(&["en", "de"]);
+/// assert_eq!(roles.role_of("Support+signup@example.com"), Some("support"));
+/// assert!(roles.is_role("kontakt@example.de"));
+/// assert!(roles.is_role("do_not_reply@example.com"));
+/// for person in ["jane.doe", "dev.patel", "hr_jones", "mail.jane", "team.lead", "it-smith"] {
+///     assert!(!roles.is_role(&format!("{}@example.com", person)));
+/// }
+///
+/// roles.insert("reservations");
+/// assert!(roles.is_role("reservations@example.com"));
+///
+/// let roles = roles.match_prefixes(true);
+/// assert_eq!(roles.role_of("sales-emea@example.com"), Some("sales"));
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct RoleList {
+    local_parts: HashSet<String>,
+    match_prefixes: bool,
+}
+
+impl RoleList {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        RoleList::default()
+    }
+
+    /// The bundled English role local parts, such as `admin`, `info`,
+    /// `support` and `noreply`.
+    pub fn english() -> Self {
+        RoleList::localized(&["en"])
+    }
+
+    /// The bundled role local parts of every language in `languages`.
+    ///
+    /// [`RoleList::languages`] lists the available language codes; unknown
+    /// codes are ignored.
+    pub fn localized(languages: &[&str]) -> Self {
+        let mut list = RoleList::new();
+        for (language, text) in BUNDLED {
+            if languages.iter().any(|wanted| wanted.eq_ignore_ascii_case(language)) {
+                list.extend(RoleList::parse(text));
+            }
+        }
+        list
+    }
+
+    /// Language codes of the bundled lists.
+    pub fn languages() -> impl Iterator<Item = &'static str> {
+        BUNDLED.iter().map(|(language, _)| *language)
+    }
+
+    /// Reads a list with one local part per line. Blank lines and lines
+    /// starting with `#` are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut list = RoleList::new();
+        for line in text.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                list.insert(line);
+            }
+        }
+        list
+    }
+
+    /// Reads a list from a file in the format of [`RoleList::parse`].
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be read or is not UTF-8.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(RoleList::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Also matches local parts that start with a listed entry followed by
+    /// `.`, `-` or `_`, such as `sales.emea@`.
+    ///
+    /// Off by default, since it also matches personal addresses such as
+    /// `dev.patel@` or `hr_jones@`.
+    pub fn match_prefixes(mut self, match_prefixes: bool) -> Self {
+        self.match_prefixes = match_prefixes;
+        self
+    }
+
+    /// Adds `local_part` to the list.
+    pub fn insert(&mut self, local_part: &str) {
+        self.local_parts.insert(local_part.trim().to_lowercase());
+    }
+
+    /// Removes `local_part` from the list, returning whether it was listed.
+    pub fn remove(&mut self, local_part: &str) -> bool {
+        self.local_parts.remove(&local_part.trim().to_lowercase())
+    }
+
+    /// Adds every local part of `other`.
+    pub fn extend(&mut self, other: RoleList) {
+        self.local_parts.extend(other.local_parts);
+    }
+
+    /// Number of listed local parts.
+    pub fn len(&self) -> usize {
+        self.local_parts.len()
+    }
+
+    /// Whether no local part is listed.
+    pub fn is_empty(&self) -> bool {
+        self.local_parts.is_empty()
+    }
+
+    /// Returns the listed role that `email_address` belongs to, if any.
+    pub fn role_of(&self, email_address: &str) -> Option<&str> {
+        let email_address = email_address.trim();
+        let local_part = email_address.rsplit_once('@').map_or(email_address, |(local_part, _)| local_part);
+        let local_part = local_part.split('+').next().unwrap_or(local_part).to_lowercase();
+        if let Some(role) = self.local_parts.get(local_part.as_str()) {
+            return Some(role);
+        }
+        if !self.match_prefixes {
+            return None;
+        }
+        let prefix = local_part.split(['.', '-', '_']).next().unwrap_or(&local_part);
+        self.local_parts.get(prefix).map(String::as_str)
+    }
+
+    /// Whether `email_address` is a role address.
+    pub fn is_role(&self, email_address: &str) -> bool {
+        self.role_of(email_address).is_some()
+    }
+}
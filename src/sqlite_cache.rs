@@ -0,0 +1,167 @@
+//! Result cache persisted in a SQLite file.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::Cache;
+
+/// [`Cache`] stored in a SQLite file, so results survive restarts.
+///
+/// Several processes may open the same file; the database runs in WAL mode
+/// and waits for locks held by the others. Clones share the connection and
+/// the hit and miss counters.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "blocking", doc = "```")]
+#[cfg_attr(not(feature = "blocking"), doc = "```ignore")]
+/// use std::time::Duration;
+///
+/// use mailboxvalidator::{Cache, MailboxValidatorClient, SqliteCache};
+///
+/// let cache = SqliteCache::open_in_memory()?;
+/// cache.put("validation/single:a@example.com", "{}".to_string(), Duration::from_secs(60));
+/// assert!(cache.get("validation/single:a@example.com").is_some());
+/// assert!(cache.get("validation/single:b@example.com").is_none());
+///
+/// let stats = cache.stats()?;
+/// assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+///
+/// let client = MailboxValidatorClient::builder("YOUR_API_KEY")
+///     .cache(cache.clone())
+///     .build()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteCache {
+    conn: Arc<Mutex<Connection>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+/// Usage counts of a [`SqliteCache`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    /// Lookups answered from the cache since it was opened.
+    pub hits: u64,
+    /// Lookups that found no fresh entry since it was opened.
+    pub misses: u64,
+    /// Entries in the file, including expired ones not yet purged.
+    pub entries: u64,
+}
+
+impl SqliteCache {
+    /// Opens the cache at `path`, creating the file if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the file cannot be opened or is not a SQLite database.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        SqliteCache::init(conn)
+    }
+
+    /// Opens a cache that lives only as long as this value and its clones.
+    ///
+    /// # Errors
+    ///
+    /// * Error when SQLite cannot allocate the database.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        SqliteCache::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS mbv_cache (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                stored_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS mbv_cache_expires_at ON mbv_cache (expires_at);",
+        )?;
+        Ok(SqliteCache {
+            conn: Arc::new(Mutex::new(conn)),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Deletes expired entries, returning how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the database cannot be written.
+    pub fn purge_expired(&self) -> rusqlite::Result<usize> {
+        self.lock().execute("DELETE FROM mbv_cache WHERE expires_at <= ?1", params![now()])
+    }
+
+    /// Deletes expired entries and compacts the file to reclaim their space.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the database cannot be written.
+    pub fn vacuum(&self) -> rusqlite::Result<()> {
+        self.purge_expired()?;
+        self.lock().execute_batch("VACUUM")
+    }
+
+    /// Returns the hit and miss counts and the number of stored entries.
+    ///
+    /// # Errors
+    ///
+    /// * Error when the database cannot be read.
+    pub fn stats(&self) -> rusqlite::Result<CacheStats> {
+        let entries = self.lock().query_row("SELECT COUNT(*) FROM mbv_cache", [], |row| row.get(0))?;
+        Ok(CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Cache for SqliteCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let value = self
+            .lock()
+            .query_row(
+                "SELECT value FROM mbv_cache WHERE key = ?1 AND expires_at > ?2",
+                params![key, now()],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten();
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    fn put(&self, key: &str, value: String, ttl: Duration) {
+        let stored_at = now();
+        let expires_at = stored_at.saturating_add(i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX));
+        // A failed write only costs a credit the next time, so it is ignored.
+        let _ = self.lock().execute(
+            "INSERT OR REPLACE INTO mbv_cache (key, value, stored_at, expires_at) VALUES (?1, ?2, ?3, ?4)",
+            params![key, value, stored_at, expires_at],
+        );
+    }
+}
+
+/// Current time in seconds since the Unix epoch.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
@@ -0,0 +1,261 @@
+//! Offline "did you mean" suggestions for misspelt email domains.
+
+use std::collections::HashSet;
+
+use crate::{idn, DomainList};
+
+/// Popular email domains shipped with the crate, most popular first.
+const POPULAR_DOMAINS: &str = include_str!("../data/popular_domains.txt");
+
+/// Top-level domains shipped with the crate, most popular first.
+const POPULAR_TLDS: &str = include_str!("../data/popular_tlds.txt");
+
+/// Every top-level domain of the DNS root zone shipped with the crate.
+const ROOT_ZONE_TLDS: &str = include_str!("../data/tlds.txt");
+
+/// Domains of real mail providers shipped with the crate, besides the free
+/// providers.
+const KNOWN_DOMAINS: &str = include_str!("../data/known_domains.txt");
+
+/// Suggests corrections for misspelt domains, such as `gmail.com` for
+/// `gmial.com`.
+///
+/// A domain is corrected to the closest popular domain within a few edits,
+/// counting a swap of two neighbouring characters as one edit. Otherwise a
+/// top-level domain missing from the root zone is corrected to a popular
+/// one a single edit away, so `example.con` becomes `example.com`.
+///
+/// Popular domains, the free providers of [`DomainList::free`], the
+/// bundled domains of internet service providers and the top-level domains
+/// of the root zone are never corrected. Ties go to the more
+/// popular candidate.
+///
+/// # Examples
+///
+/// ```
+/// use mailboxvalidator::TypoSuggester;
+///
+/// let mut suggester = TypoSuggester::new();
+/// assert_eq!(suggester.suggest("jane@gmial.com").as_deref(), Some("jane@gmail.com"));
+/// assert_eq!(suggester.suggest("jane@hotmial.con").as_deref(), Some("jane@hotmail.com"));
+/// assert_eq!(suggester.suggest("jane@example.cmo").as_deref(), Some("jane@example.com"));
+/// assert_eq!(suggester.suggest("jane@yahoo.com"), None);
+/// assert_eq!(suggester.suggest("jane@example.org"), None);
+///
+/// // Real providers close to a popular domain are left alone.
+/// assert_eq!(suggester.suggest("jane@hotmail.es"), None);
+/// assert_eq!(suggester.suggest("jane@email.com"), None);
+/// assert_eq!(suggester.suggest("jane@yahoo.ca"), None);
+/// assert_eq!(suggester.suggest("jane@example.cat"), None);
+///
+/// suggester.insert_domain("mail.example");
+/// assert_eq!(suggester.suggest_domain("mial.example").as_deref(), Some("mail.example"));
+/// assert_eq!(suggester.suggest_domain("gmial.com").as_deref(), Some("gmail.com"));
+///
+/// assert_eq!(suggester.suggest_domain("gmal.com").as_deref(), Some("gmail.com"));
+/// suggester.insert_known_domain("gmal.com");
+/// assert_eq!(suggester.suggest_domain("gmal.com"), None);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct TypoSuggester {
+    domains: Vec<String>,
+    tlds: Vec<String>,
+    known_tlds: HashSet<String>,
+    known: DomainList,
+    max_distance: usize,
+}
+
+impl TypoSuggester {
+    /// Creates a suggester with the popular domains, top-level domains and
+    /// known provider domains bundled with this version of the crate,
+    /// allowing up to 2 edits.
+    pub fn new() -> Self {
+        let mut known = DomainList::free();
+        known.extend(DomainList::parse(KNOWN_DOMAINS));
+        TypoSuggester {
+            domains: parse(POPULAR_DOMAINS),
+            tlds: parse(POPULAR_TLDS),
+            known_tlds: parse(ROOT_ZONE_TLDS).into_iter().collect(),
+            known,
+            max_distance: 2,
+        }
+    }
+
+    /// Sets the most edits a domain may be away from a popular domain.
+    ///
+    /// Short domains allow fewer, one per four characters, so that
+    /// unrelated short domains are not corrected. Defaults to 2.
+    pub fn max_distance(mut self, max_distance: usize) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Adds `domain` as a candidate, after the bundled ones.
+    pub fn insert_domain(&mut self, domain: &str) {
+        let domain = normalize(domain);
+        if !self.domains.contains(&domain) {
+            self.domains.push(domain);
+        }
+    }
+
+    /// Adds `tld` as a known top-level domain and as a candidate, after the
+    /// bundled ones.
+    pub fn insert_tld(&mut self, tld: &str) {
+        let tld = normalize(tld.trim_start_matches('.'));
+        self.known_tlds.insert(to_ascii(&tld));
+        if !self.tlds.contains(&tld) {
+            self.tlds.push(tld);
+        }
+    }
+
+    /// Adds `domain` as a known domain, which is never corrected.
+    pub fn insert_known_domain(&mut self, domain: &str) {
+        self.known.insert(domain);
+    }
+
+    /// Adds every domain of `domains` as a known domain, see
+    /// [`TypoSuggester::insert_known_domain`].
+    pub fn extend_known_domains(&mut self, domains: DomainList) {
+        self.known.extend(domains);
+    }
+
+    /// Returns `email_address` with its domain corrected, or `None` if the
+    /// domain looks right or has no close candidate.
+    pub fn suggest(&self, email_address: &str) -> Option<String> {
+        let (local_part, domain) = email_address.trim().rsplit_once('@')?;
+        if local_part.is_empty() {
+            return None;
+        }
+        let domain = self.suggest_domain(domain)?;
+        Some(format!("{}@{}", local_part, domain))
+    }
+
+    /// Returns the correction of `domain`, or `None` if it looks right or
+    /// has no close candidate.
+    pub fn suggest_domain(&self, domain: &str) -> Option<String> {
+        let domain = normalize(domain);
+        if domain.is_empty() || self.domains.contains(&domain) || self.known.contains(&domain) {
+            return None;
+        }
+        let allowed = self.max_distance.min(domain.chars().count() / 4);
+        let closest = self
+            .domains
+            .iter()
+            .map(|candidate| (edit_distance(&domain, candidate), candidate))
+            .filter(|(distance, _)| *distance <= allowed)
+            .min_by_key(|(distance, _)| *distance);
+        if let Some((_, candidate)) = closest {
+            return Some(candidate.clone());
+        }
+
+        let (name, tld) = domain.rsplit_once('.')?;
+        if name.is_empty() || self.known_tlds.contains(&to_ascii(tld)) {
+            return None;
+        }
+        let tld = self.tlds.iter().find(|candidate| edit_distance(tld, candidate) == 1)?;
+        Some(format!("{}.{}", name, tld))
+    }
+}
+
+impl Default for TypoSuggester {
+    fn default() -> Self {
+        TypoSuggester::new()
+    }
+}
+
+/// Reads one entry per line, skipping blank lines and `#` comments.
+fn parse(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(normalize)
+        .collect()
+}
+
+fn normalize(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// The punycode form of a top-level domain, as listed in the root zone.
+fn to_ascii(tld: &str) -> String {
+    idn::domain_to_ascii(tld).unwrap_or_else(|| tld.to_string())
+}
+
+/// Number of insertions, deletions, substitutions and swaps of neighbouring
+/// characters turning `a` into `b` (optimal string alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Rows i - 2, i - 1 and i of the distance matrix.
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, TypoSuggester, KNOWN_DOMAINS, POPULAR_DOMAINS};
+    use crate::DomainList;
+
+    #[test]
+    fn popular_domains_are_free_or_known_providers() {
+        let free = DomainList::free();
+        let known = DomainList::parse(KNOWN_DOMAINS);
+        for domain in parse(POPULAR_DOMAINS) {
+            assert!(free.contains(&domain) || known.contains(&domain), "{} is in neither list", domain);
+        }
+        for domain in parse(KNOWN_DOMAINS) {
+            assert!(!free.contains(&domain), "{} is free, list it in free_domains.txt only", domain);
+        }
+    }
+
+    #[test]
+    fn real_top_level_domains_are_not_corrected() {
+        let suggester = TypoSuggester::new();
+        for domain in ["x.cat", "x.bio", "x.ong", "x.moe", "x.law", "x.wtf", "x.рф", "x.xn--p1ai"] {
+            assert_eq!(suggester.suggest_domain(domain), None, "{}", domain);
+        }
+    }
+
+    #[test]
+    fn known_provider_domains_are_not_corrected() {
+        let suggester = TypoSuggester::new();
+        for domain in ["yahoo.ca", "hotmail.nl", "outlook.fr", "live.nl", "gmx.fr", "yahoo.com.mx", "comcast.net"] {
+            assert_eq!(suggester.suggest_domain(domain), None, "{}", domain);
+        }
+        // Known domains are left alone but are not candidates themselves.
+        assert_eq!(suggester.suggest_domain("yahooo.ca"), None);
+        assert_eq!(suggester.suggest_domain("hotmial.fr").as_deref(), Some("hotmail.fr"));
+    }
+
+    #[test]
+    fn unknown_top_level_domains_are_corrected_to_popular_ones() {
+        let suggester = TypoSuggester::new();
+        assert_eq!(suggester.suggest_domain("x.con").as_deref(), Some("x.com"));
+        assert_eq!(suggester.suggest_domain("x.ogr").as_deref(), Some("x.org"));
+        assert_eq!(suggester.suggest_domain("x.nte").as_deref(), Some("x.net"));
+        assert_eq!(suggester.suggest_domain("x.qwerty"), None);
+    }
+
+    #[test]
+    fn inserted_top_level_domains_are_known_and_candidates() {
+        let mut suggester = TypoSuggester::new();
+        assert_eq!(suggester.suggest_domain("x.internall"), None);
+        suggester.insert_tld(".internal");
+        assert_eq!(suggester.suggest_domain("x.internal"), None);
+        assert_eq!(suggester.suggest_domain("x.internall").as_deref(), Some("x.internal"));
+    }
+}
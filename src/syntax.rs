@@ -0,0 +1,553 @@
+//! Offline email address syntax checks.
+//!
+//! [`parse`] accepts the `addr-spec` of RFC 5322 with the domain rules of
+//! RFC 5321: dot-atom or quoted local parts, comments and folding white
+//! space around either part, host names, and IPv4, IPv6 or general address
+//! literals. UTF-8 is allowed in atoms as in RFC 6531, and host names may
+//! be internationalized domain names, whose length limits apply to their
+//! ASCII (punycode) form. The obsolete syntax of RFC 5322 section 4 is
+//! rejected, as mail servers no longer accept it.
+//!
+//! # Examples
+//!
+//! ```
+//! use mailboxvalidator::syntax::{self, SyntaxError};
+//!
+//! let address = syntax::parse("\"john doe\"@example.com")?;
+//! assert_eq!(address.local_part(), "\"john doe\"");
+//! assert_eq!(address.domain(), "example.com");
+//!
+//! let address = syntax::parse("john(work)@[192.0.2.1]")?;
+//! assert_eq!(address.to_string(), "john@[192.0.2.1]");
+//! assert!(address.is_domain_literal());
+//!
+//! let address = syntax::parse("jürgen@Bücher.example")?;
+//! assert_eq!(address.domain_ascii(), "xn--bcher-kva.example");
+//! assert_eq!(address.domain_unicode(), "bücher.example");
+//!
+//! assert_eq!(syntax::parse("john..doe@example.com"), Err(SyntaxError::InvalidDot));
+//! assert_eq!(syntax::parse("john@-example.com"), Err(SyntaxError::InvalidLabel));
+//! assert!(!syntax::is_valid("john doe@example.com"));
+//! # Ok::<(), SyntaxError>(())
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::idn;
+
+/// Longest local part, in octets (RFC 5321 section 4.5.3.1.1).
+const MAX_LOCAL_PART: usize = 64;
+/// Longest domain, in octets (RFC 5321 section 4.5.3.1.2).
+const MAX_DOMAIN: usize = 255;
+/// Longest address that fits a 256-octet SMTP path with its angle brackets.
+const MAX_ADDRESS: usize = 254;
+/// Longest domain label, in octets (RFC 1035 section 2.3.4).
+const MAX_LABEL: usize = 63;
+
+/// A syntactically valid email address, without comments or white space.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Address {
+    local_part: String,
+    domain: String,
+    domain_ascii: String,
+    domain_unicode: String,
+    domain_literal: bool,
+}
+
+impl Address {
+    /// The part before the `@`, quoted if it was written quoted.
+    pub fn local_part(&self) -> &str {
+        &self.local_part
+    }
+
+    /// The part after the `@`, including the brackets of a domain literal.
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// The domain in ASCII form, with internationalized labels in punycode
+    /// and in lowercase. Domain literals are returned as written.
+    pub fn domain_ascii(&self) -> &str {
+        &self.domain_ascii
+    }
+
+    /// The domain in Unicode form, with punycode labels decoded and in
+    /// lowercase. Domain literals are returned as written.
+    pub fn domain_unicode(&self) -> &str {
+        &self.domain_unicode
+    }
+
+    /// Whether the domain is an address literal such as `[192.0.2.1]`.
+    pub fn is_domain_literal(&self) -> bool {
+        self.domain_literal
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.local_part, self.domain)
+    }
+}
+
+/// Why an address was rejected by [`parse`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SyntaxError {
+    /// The input is empty or only white space.
+    Empty,
+    /// There is no `@` between the local part and the domain.
+    MissingAt,
+    /// Nothing comes before the `@`.
+    EmptyLocalPart,
+    /// Nothing comes after the `@`.
+    EmptyDomain,
+    /// A dot starts or ends the local part or the domain, or follows another dot.
+    InvalidDot,
+    /// A character that is not allowed where it appears.
+    InvalidCharacter(char),
+    /// A quoted local part is missing its closing quote.
+    UnterminatedQuote,
+    /// A comment is missing its closing parenthesis.
+    UnterminatedComment,
+    /// A domain literal is missing its closing bracket.
+    UnterminatedDomainLiteral,
+    /// A domain literal is not an IPv4, IPv6 or general address literal.
+    InvalidDomainLiteral,
+    /// A domain label is empty, starts or ends with a hyphen, or has
+    /// characters other than letters, digits and hyphens in its ASCII form,
+    /// the top-level label is numeric, or an internationalized label is not
+    /// allowed by IDNA.
+    InvalidLabel,
+    /// The local part is longer than 64 octets.
+    LocalPartTooLong,
+    /// A domain label is longer than 63 octets.
+    LabelTooLong,
+    /// The domain is longer than 255 octets.
+    DomainTooLong,
+    /// The address is longer than 254 octets.
+    AddressTooLong,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxError::Empty => write!(f, "email address is empty"),
+            SyntaxError::MissingAt => write!(f, "email address has no @"),
+            SyntaxError::EmptyLocalPart => write!(f, "email address has nothing before the @"),
+            SyntaxError::EmptyDomain => write!(f, "email address has nothing after the @"),
+            SyntaxError::InvalidDot => write!(f, "misplaced dot in email address"),
+            SyntaxError::InvalidCharacter(c) => write!(f, "invalid character {:?} in email address", c),
+            SyntaxError::UnterminatedQuote => write!(f, "unterminated quoted string in email address"),
+            SyntaxError::UnterminatedComment => write!(f, "unterminated comment in email address"),
+            SyntaxError::UnterminatedDomainLiteral => write!(f, "unterminated domain literal in email address"),
+            SyntaxError::InvalidDomainLiteral => write!(f, "invalid domain literal in email address"),
+            SyntaxError::InvalidLabel => write!(f, "invalid domain label in email address"),
+            SyntaxError::LocalPartTooLong => write!(f, "local part of email address is longer than {} octets", MAX_LOCAL_PART),
+            SyntaxError::LabelTooLong => write!(f, "domain label is longer than {} octets", MAX_LABEL),
+            SyntaxError::DomainTooLong => write!(f, "domain of email address is longer than {} octets", MAX_DOMAIN),
+            SyntaxError::AddressTooLong => write!(f, "email address is longer than {} octets", MAX_ADDRESS),
+        }
+    }
+}
+
+impl Error for SyntaxError {}
+
+/// Parses `input` as an email address.
+///
+/// # Errors
+///
+/// * The first problem found, see [`SyntaxError`].
+pub fn parse(input: &str) -> Result<Address, SyntaxError> {
+    if input.trim().is_empty() {
+        return Err(SyntaxError::Empty);
+    }
+    let mut parser = Parser { input, pos: 0 };
+
+    parser.skip_cfws()?;
+    let local_part = match parser.peek() {
+        Some('"') => parser.quoted_string()?,
+        Some('@') => return Err(SyntaxError::EmptyLocalPart),
+        _ => parser.dot_atom(is_atext)?,
+    };
+    parser.skip_cfws()?;
+    match parser.bump() {
+        Some('@') => {}
+        Some(c) => return Err(SyntaxError::InvalidCharacter(c)),
+        None => return Err(SyntaxError::MissingAt),
+    }
+
+    parser.skip_cfws()?;
+    let (domain, domain_literal) = match parser.peek() {
+        None => return Err(SyntaxError::EmptyDomain),
+        Some('[') => (parser.domain_literal()?, true),
+        _ => (parser.dot_atom(is_host_char)?, false),
+    };
+    parser.skip_cfws()?;
+    if let Some(c) = parser.peek() {
+        return Err(SyntaxError::InvalidCharacter(c));
+    }
+
+    if local_part.len() > MAX_LOCAL_PART {
+        return Err(SyntaxError::LocalPartTooLong);
+    }
+    let (domain_ascii, domain_unicode) = if domain_literal {
+        (domain.clone(), domain.clone())
+    } else {
+        let ascii = idn::domain_to_ascii(&domain).ok_or(SyntaxError::InvalidLabel)?;
+        check_host_name(&ascii)?;
+        let unicode = idn::domain_to_unicode(&ascii);
+        (ascii, unicode)
+    };
+    if domain_ascii.len() > MAX_DOMAIN {
+        return Err(SyntaxError::DomainTooLong);
+    }
+    if local_part.len() + 1 + domain_ascii.len() > MAX_ADDRESS {
+        return Err(SyntaxError::AddressTooLong);
+    }
+
+    Ok(Address {
+        local_part,
+        domain,
+        domain_ascii,
+        domain_unicode,
+        domain_literal,
+    })
+}
+
+/// Whether `input` is a syntactically valid email address.
+pub fn is_valid(input: &str) -> bool {
+    parse(input).is_ok()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Whether a line break that folds white space comes next: `CRLF`
+    /// followed by a space or a tab (RFC 5322 section 3.2.2).
+    fn at_fold(&self) -> bool {
+        let rest = &self.input[self.pos..];
+        rest.starts_with("\r\n ") || rest.starts_with("\r\n\t")
+    }
+
+    /// Skips comments and folding white space.
+    fn skip_cfws(&mut self) -> Result<(), SyntaxError> {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t') => {
+                    self.bump();
+                }
+                Some('\r') if self.at_fold() => self.pos += 2,
+                Some('(') => self.comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Skips a comment, which may nest and contain quoted pairs.
+    fn comment(&mut self) -> Result<(), SyntaxError> {
+        self.bump();
+        let mut depth = 1;
+        while depth > 0 {
+            if self.at_fold() {
+                self.pos += 2;
+                continue;
+            }
+            match self.bump() {
+                None => return Err(SyntaxError::UnterminatedComment),
+                Some('(') => depth += 1,
+                Some(')') => depth -= 1,
+                Some('\\') => {
+                    self.bump().ok_or(SyntaxError::UnterminatedComment)?;
+                }
+                Some(c) if c.is_ascii_control() && !matches!(c, ' ' | '\t') => {
+                    return Err(SyntaxError::InvalidCharacter(c));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads dot-separated runs of characters accepted by `allowed`.
+    fn dot_atom(&mut self, allowed: fn(char) -> bool) -> Result<String, SyntaxError> {
+        let start = self.pos;
+        let mut after_dot = true;
+        while let Some(c) = self.peek() {
+            if c == '.' {
+                if after_dot {
+                    return Err(SyntaxError::InvalidDot);
+                }
+                after_dot = true;
+            } else if allowed(c) {
+                after_dot = false;
+            } else {
+                break;
+            }
+            self.bump();
+        }
+        if after_dot && self.pos > start {
+            return Err(SyntaxError::InvalidDot);
+        }
+        if after_dot {
+            return Err(self.peek().map_or(SyntaxError::EmptyDomain, SyntaxError::InvalidCharacter));
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    /// Reads a quoted local part, keeping its quotes and escapes.
+    fn quoted_string(&mut self) -> Result<String, SyntaxError> {
+        let start = self.pos;
+        self.bump();
+        loop {
+            match self.bump() {
+                None => return Err(SyntaxError::UnterminatedQuote),
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some(c) if is_vchar(c) || c == ' ' || c == '\t' => {}
+                    Some(c) => return Err(SyntaxError::InvalidCharacter(c)),
+                    None => return Err(SyntaxError::UnterminatedQuote),
+                },
+                Some(c) if is_vchar(c) || c == ' ' || c == '\t' => {}
+                Some(c) => return Err(SyntaxError::InvalidCharacter(c)),
+            }
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    /// Reads and checks a bracketed address literal.
+    fn domain_literal(&mut self) -> Result<String, SyntaxError> {
+        let start = self.pos;
+        self.bump();
+        loop {
+            match self.bump() {
+                None => return Err(SyntaxError::UnterminatedDomainLiteral),
+                Some(']') => break,
+                Some(c) if c.is_ascii_graphic() && !matches!(c, '[' | '\\') => {}
+                Some(c) => return Err(SyntaxError::InvalidCharacter(c)),
+            }
+        }
+        let literal = &self.input[start..self.pos];
+        if !is_address_literal(&literal[1..literal.len() - 1]) {
+            return Err(SyntaxError::InvalidDomainLiteral);
+        }
+        Ok(literal.to_string())
+    }
+}
+
+/// Checks the label rules of RFC 5321 host names on the ASCII form of a
+/// domain.
+fn check_host_name(domain: &str) -> Result<(), SyntaxError> {
+    for label in domain.split('.') {
+        if label.len() > MAX_LABEL {
+            return Err(SyntaxError::LabelTooLong);
+        }
+        // IDNA maps some characters to nothing or to a space, so check the
+        // ASCII form against the letter, digit and hyphen rule again.
+        if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(SyntaxError::InvalidLabel);
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(SyntaxError::InvalidLabel);
+        }
+    }
+    let top = domain.rsplit('.').next().unwrap_or(domain);
+    if domain.contains('.') && top.chars().all(|c| c.is_ascii_digit()) {
+        return Err(SyntaxError::InvalidLabel);
+    }
+    Ok(())
+}
+
+/// IPv4, `IPv6:` or general address literal (RFC 5321 section 4.1.3).
+fn is_address_literal(content: &str) -> bool {
+    if content.parse::<Ipv4Addr>().is_ok() {
+        return true;
+    }
+    if let Some(ipv6) = content.get(..5).filter(|tag| tag.eq_ignore_ascii_case("IPv6:")) {
+        return content[ipv6.len()..].parse::<Ipv6Addr>().is_ok();
+    }
+    match content.split_once(':') {
+        Some((tag, value)) => {
+            !tag.is_empty()
+                && !value.is_empty()
+                && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !tag.ends_with('-')
+        }
+        None => false,
+    }
+}
+
+/// Characters allowed in an atom (RFC 5322 section 3.2.3, RFC 6531).
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii()
+}
+
+/// Characters allowed in a host name label, including the UTF-8 of
+/// internationalized labels (RFC 6531).
+fn is_host_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || !c.is_ascii()
+}
+
+/// Visible characters, including UTF-8 (RFC 5234, RFC 6532).
+fn is_vchar(c: char) -> bool {
+    c.is_ascii_graphic() || !c.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid, parse, SyntaxError};
+
+    fn repeat(c: char, n: usize) -> String {
+        c.to_string().repeat(n)
+    }
+
+    #[test]
+    fn local_part_is_limited_to_64_octets() {
+        assert!(is_valid(&format!("{}@example.com", repeat('a', 64))));
+        assert_eq!(parse(&format!("{}@example.com", repeat('a', 65))), Err(SyntaxError::LocalPartTooLong));
+        // Quotes count towards the limit, and so do the octets of UTF-8 characters.
+        assert!(is_valid(&format!("\"{}\"@example.com", repeat('a', 62))));
+        assert_eq!(parse(&format!("\"{}\"@example.com", repeat('a', 63))), Err(SyntaxError::LocalPartTooLong));
+        assert!(is_valid(&format!("{}@example.com", repeat('é', 32))));
+        assert_eq!(parse(&format!("{}a@example.com", repeat('é', 32))), Err(SyntaxError::LocalPartTooLong));
+    }
+
+    #[test]
+    fn labels_are_limited_to_63_octets() {
+        assert!(is_valid(&format!("a@{}.com", repeat('b', 63))));
+        assert_eq!(parse(&format!("a@{}.com", repeat('b', 64))), Err(SyntaxError::LabelTooLong));
+        assert_eq!(parse(&format!("a@example.{}", repeat('c', 64))), Err(SyntaxError::LabelTooLong));
+    }
+
+    #[test]
+    fn address_is_limited_to_254_octets() {
+        let domain = |last: usize| format!("{}.{}.{}", repeat('b', 63), repeat('c', 63), repeat('d', last));
+        assert!(is_valid(&format!("{}@{}", repeat('a', 64), domain(61))));
+        assert_eq!(parse(&format!("{}@{}", repeat('a', 64), domain(62))), Err(SyntaxError::AddressTooLong));
+    }
+
+    #[test]
+    fn domain_is_limited_to_255_octets() {
+        let label = repeat('b', 63);
+        let domain = format!("{0}.{0}.{0}.{0}", label);
+        assert_eq!(domain.len(), 255);
+        assert_eq!(parse(&format!("a@{}", domain)), Err(SyntaxError::AddressTooLong));
+        assert_eq!(parse(&format!("a@{}b", domain)), Err(SyntaxError::LabelTooLong));
+        assert_eq!(parse(&format!("a@{}.b", domain)), Err(SyntaxError::DomainTooLong));
+    }
+
+    #[test]
+    fn dots_separate_atoms() {
+        assert!(is_valid("john.q.doe@mail.example.com"));
+        for (input, error) in [
+            (".john@example.com", SyntaxError::InvalidDot),
+            ("john.@example.com", SyntaxError::InvalidDot),
+            ("john..doe@example.com", SyntaxError::InvalidDot),
+            ("john@.example.com", SyntaxError::InvalidDot),
+            ("john@example.com.", SyntaxError::InvalidDot),
+            ("john@example..com", SyntaxError::InvalidDot),
+            ("john@.", SyntaxError::InvalidDot),
+            (".@example.com", SyntaxError::InvalidDot),
+        ] {
+            assert_eq!(parse(input), Err(error), "{}", input);
+        }
+    }
+
+    #[test]
+    fn folding_white_space_is_crlf_followed_by_a_space_or_tab() {
+        for (input, address) in [
+            ("john\r\n @example.com", "john@example.com"),
+            ("john@\r\n\texample.com", "john@example.com"),
+            ("john(work\r\n day)@example.com", "john@example.com"),
+        ] {
+            assert_eq!(parse(input).map(|address| address.to_string()).as_deref(), Ok(address), "{:?}", input);
+        }
+        for (input, error) in [
+            ("john\n@example.com", SyntaxError::InvalidCharacter('\n')),
+            ("john\r@example.com", SyntaxError::InvalidCharacter('\r')),
+            ("john@example.com\r\n", SyntaxError::InvalidCharacter('\r')),
+            ("john\r\n@example.com", SyntaxError::InvalidCharacter('\r')),
+            ("john(work\nday)@example.com", SyntaxError::InvalidCharacter('\n')),
+        ] {
+            assert_eq!(parse(input), Err(error), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn quoted_local_parts() {
+        for input in [
+            "\"john doe\"@example.com",
+            "\"john..doe\"@example.com",
+            "\".john.\"@example.com",
+            "\"john@doe\"@example.com",
+            "\"john\\\"doe\"@example.com",
+            "\"john\\\\\"@example.com",
+            "\"\"@example.com",
+            " \"john\" (comment) @example.com",
+        ] {
+            assert!(is_valid(input), "{}", input);
+        }
+        assert_eq!(parse("\"john doe\"@example.com").unwrap().local_part(), "\"john doe\"");
+        assert_eq!(parse("\"john\\\"doe\"@example.com").unwrap().local_part(), "\"john\\\"doe\"");
+
+        assert_eq!(parse("\"john@example.com"), Err(SyntaxError::UnterminatedQuote));
+        assert_eq!(parse("\"john\\"), Err(SyntaxError::UnterminatedQuote));
+        assert_eq!(parse("\"john\"doe@example.com"), Err(SyntaxError::InvalidCharacter('d')));
+        assert_eq!(parse("john.\"doe\"@example.com"), Err(SyntaxError::InvalidDot));
+        assert_eq!(parse("\"john\u{7}\"@example.com"), Err(SyntaxError::InvalidCharacter('\u{7}')));
+        assert_eq!(parse("\"john\ndoe\"@example.com"), Err(SyntaxError::InvalidCharacter('\n')));
+    }
+
+    #[test]
+    fn host_names_are_letters_digits_and_hyphens_in_ascii() {
+        for input in ["a@\u{a0}b.com", "a@b\u{3000}c.com", "a@\u{200b}.com", "a@b\u{200b}.\u{200b}.com"] {
+            assert_eq!(parse(input), Err(SyntaxError::InvalidLabel), "{:?}", input);
+        }
+        assert_eq!(parse("a@B\u{fc}cher-1.example").unwrap().domain_ascii(), "xn--bcher-1-n2a.example");
+        assert_eq!(parse("a@b.c-d.com").unwrap().domain_ascii(), "b.c-d.com");
+    }
+
+    #[test]
+    fn domain_literals() {
+        for (input, domain) in [
+            ("a@[192.0.2.1]", "[192.0.2.1]"),
+            ("a@[IPv6:2001:db8::1]", "[IPv6:2001:db8::1]"),
+            ("a@[ipv6:::1]", "[ipv6:::1]"),
+            ("a@[x400:c=us;a=att]", "[x400:c=us;a=att]"),
+            ("a@ [192.0.2.1] ", "[192.0.2.1]"),
+        ] {
+            let address = parse(input).unwrap();
+            assert!(address.is_domain_literal(), "{}", input);
+            assert_eq!((address.domain(), address.domain_ascii()), (domain, domain));
+        }
+        assert!(!parse("a@example.com").unwrap().is_domain_literal());
+
+        for (input, error) in [
+            ("a@[192.0.2.256]", SyntaxError::InvalidDomainLiteral),
+            ("a@[192.0.2]", SyntaxError::InvalidDomainLiteral),
+            ("a@[IPv6:2001:db8::g]", SyntaxError::InvalidDomainLiteral),
+            ("a@[tag-:value]", SyntaxError::InvalidDomainLiteral),
+            ("a@[:value]", SyntaxError::InvalidDomainLiteral),
+            ("a@[]", SyntaxError::InvalidDomainLiteral),
+            ("a@[192.0.2.1", SyntaxError::UnterminatedDomainLiteral),
+            ("a@[192.0.2.1]x", SyntaxError::InvalidCharacter('x')),
+            ("a@[192.0 .2.1]", SyntaxError::InvalidCharacter(' ')),
+            ("a@[a[b]", SyntaxError::InvalidCharacter('[')),
+        ] {
+            assert_eq!(parse(input), Err(error), "{}", input);
+        }
+    }
+}